@@ -1,4 +1,4 @@
-use v39::{self, prelude::*};
+use v39::{self, prelude::*, logging::{FileSink, LevelFilter}};
 
 
 struct App
@@ -15,7 +15,7 @@ impl EventReceiver for App
         Ok(())
     }
 
-    fn key_down(&mut self, key: input::V39Key) -> V39Result<()>
+    fn key_down(&mut self, key: input::V39Key, _mods: input::Modifiers) -> V39Result<()>
     {
         match key
         {
@@ -39,17 +39,21 @@ impl EventReceiver for App
         Ok(())
     }
 
-    fn quit(&mut self, reason: u32) -> V39Result<()> 
+    fn quit(&mut self, reason: &QuitReason) -> V39Result<()> 
     {
-        println!("Quitting because of reason: {reason}");
+        println!("Quitting with code: {}", reason.code);
         Ok(())
     }
 }
 
 
-fn main() -> V39Result<()>
+fn main() -> V39Result<ExitStatus>
 {
-    let app = v39::init()?;
+    let log = LogConfig::new()
+        .module("v39::renderer", LevelFilter::Info)
+        .sink(FileSink::new("logs/sample_app.log")?);
+
+    let app = v39::init_with(EngineConfig::new().log(log))?;
     
     let my_app = App {
         fps_cap: Some(60),
@@ -124,7 +128,7 @@ impl EventReceiver for CustomEventReceiver
         Ok(())
     }
 
-    fn key_down(&mut self, key: input::V39Key) -> V39Result<()> 
+    fn key_down(&mut self, key: input::V39Key, _mods: input::Modifiers) -> V39Result<()> 
     {
         if key == input::V39Key::A
         {
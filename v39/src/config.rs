@@ -0,0 +1,24 @@
+use crate::logging::LogConfig;
+
+
+/// Settings of the engine, passed to `v39::init_with`.
+#[derive(Default)]
+pub struct EngineConfig
+{
+    pub(crate) log: LogConfig,
+}
+
+
+impl EngineConfig
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn log(mut self, log: LogConfig) -> Self
+    {
+        self.log = log;
+        self
+    }
+}
@@ -0,0 +1,403 @@
+use once_cell::sync::OnceCell;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use crate::event::EngineEvent;
+use crate::logging::{Logger, LogFormat};
+use crate::prelude::*;
+use crate::window::WindowId;
+
+
+static INSTANCE: OnceCell<CrashHandler> = OnceCell::new();
+
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub(crate) const DEFAULT_EVENTS: usize = 100;
+pub(crate) const DEFAULT_REPORT_DIR: &str = "crash_reports";
+
+
+/// The last entries, the oldest are dropped once `capacity` is reached.
+pub(crate) struct History<T>
+{
+    entries: VecDeque<T>,
+    capacity: usize,
+}
+
+
+impl<T: Clone> History<T>
+{
+    pub(crate) fn new(capacity: usize) -> Self
+    {
+        Self {entries: VecDeque::new(), capacity}
+    }
+
+    pub(crate) fn push(&mut self, entry: T)
+    {
+        if self.capacity == 0 {return}
+
+        while self.entries.len() >= self.capacity
+        {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize)
+    {
+        self.capacity = capacity;
+
+        while self.entries.len() > self.capacity
+        {
+            self.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn entries(&self) -> Vec<T>
+    {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+
+/// An event as it was dispatched, only formatted once a report is written.
+#[derive(Clone, Debug)]
+pub(crate) enum RecordedEvent
+{
+    Engine(EngineEvent, WindowId),
+    User(Event),
+}
+
+
+impl RecordedEvent
+{
+    fn format(&self, elapsed: f64) -> String
+    {
+        match self
+        {
+            RecordedEvent::Engine(event, window) => format!("[{elapsed:.3}s] {event:?} in {window:?}"),
+            RecordedEvent::User(event) => format!("[{elapsed:.3}s] {event:?}"),
+        }
+    }
+}
+
+
+/// What is known about a panic, written to a file by the panic hook.
+#[derive(Clone, Debug)]
+pub struct CrashReport
+{
+    pub time: chrono::DateTime<chrono::Local>,
+    pub engine_version: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+
+    /// Summary of the graphics device, `None` if the renderer did not get to pick one.
+    pub device: Option<String>,
+
+    /// The last lines of the engine's log buffer and the last engine events, oldest first.
+    pub log: Vec<String>,
+    pub events: Vec<String>,
+}
+
+
+impl CrashReport
+{
+    pub fn write(&self, out: &mut impl Write) -> std::io::Result<()>
+    {
+        writeln!(out, "v39 crash report")?;
+        writeln!(out)?;
+        writeln!(out, "Time: {}", self.time.format("%Y-%m-%d %H:%M:%S%.3f %:z"))?;
+        writeln!(out, "Engine version: {}", self.engine_version)?;
+        writeln!(out, "Thread: {}", self.thread)?;
+        writeln!(out, "Panic: {}", self.message)?;
+        writeln!(out, "Location: {}", self.location.as_deref().unwrap_or("unknown"))?;
+
+        section(out, "Device", self.device.as_deref().unwrap_or("No device was selected").lines())?;
+        section(out, "Backtrace", self.backtrace.lines())?;
+        section(out, "Last events", self.events.iter().map(String::as_str))?;
+        section(out, "Last log lines", self.log.iter().map(String::as_str))
+    }
+
+    fn file_name(&self, attempt: u32) -> String
+    {
+        match attempt
+        {
+            0 => format!("crash-{}.txt", self.time.format("%Y%m%d-%H%M%S")),
+            n => format!("crash-{}-{n}.txt", self.time.format("%Y%m%d-%H%M%S")),
+        }
+    }
+
+    /// Writes the report into a new file in `dir` and returns its path.
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> std::io::Result<PathBuf>
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        // Several threads may panic within the same second
+        let mut attempt = 0;
+
+        let (path, file) = loop
+        {
+            let path = dir.join(self.file_name(attempt));
+
+            match std::fs::File::options().write(true).create_new(true).open(&path)
+            {
+                Ok(file) => break (path, file),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+                Err(e) => return Err(e),
+            }
+        };
+
+        let mut out = std::io::BufWriter::new(file);
+        self.write(&mut out)?;
+        out.flush()?;
+
+        Ok(path)
+    }
+}
+
+
+fn section<'a>(out: &mut impl Write, title: &str, lines: impl Iterator<Item = &'a str>) -> std::io::Result<()>
+{
+    writeln!(out)?;
+    writeln!(out, "{title}")?;
+    writeln!(out, "{}", "-".repeat(title.len()))?;
+
+    let mut empty = true;
+
+    for line in lines
+    {
+        writeln!(out, "{line}")?;
+        empty = false;
+    }
+
+    if empty {writeln!(out, "None")?}
+    Ok(())
+}
+
+
+/// The message a panic was started with, if it was a string.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String
+{
+    if let Some(message) = payload.downcast_ref::<&str>()
+    {
+        return message.to_string();
+    }
+
+    match payload.downcast_ref::<String>()
+    {
+        Some(message) => message.clone(),
+        None => "Box<dyn Any>".into(),
+    }
+}
+
+
+pub(crate) struct CrashHandler
+{
+    start: Instant,
+    report_dir: Mutex<PathBuf>,
+    device: Mutex<Option<String>>,
+    events: Mutex<History<(f64, RecordedEvent)>>,
+    last_report: Mutex<Option<PathBuf>>,
+}
+
+
+impl CrashHandler
+{
+    /// Installs the panic hook, the previous hook still runs after the report was written.
+    pub(crate) fn init() -> V39Result<&'static Self>
+    {
+        let handler = CrashHandler {
+            start: Instant::now(),
+            report_dir: Mutex::new(PathBuf::from(DEFAULT_REPORT_DIR)),
+            device: Mutex::new(None),
+            events: Mutex::new(History::new(DEFAULT_EVENTS)),
+            last_report: Mutex::new(None),
+        };
+
+        if INSTANCE.set(handler).is_err()
+        {
+            return Err(V39Error::Reinit("Crash Handler".into()));
+        }
+
+        let previous = std::panic::take_hook();
+
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(handler) = CrashHandler::get()
+            {
+                handler.on_panic(info.payload(), info.location());
+            }
+
+            previous(info);
+        }));
+
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    pub(crate) fn get() -> Option<&'static Self>
+    {
+        INSTANCE.get()
+    }
+
+    /// Called for every dispatched event, so the event is only formatted if a report is written.
+    pub(crate) fn record_event(&self, event: RecordedEvent)
+    {
+        if let Ok(mut events) = self.events.lock()
+        {
+            events.push((self.start.elapsed().as_secs_f64(), event));
+        }
+    }
+
+    pub(crate) fn set_device(&self, device: String)
+    {
+        if let Ok(mut current) = self.device.lock()
+        {
+            *current = Some(device);
+        }
+    }
+
+    pub(crate) fn set_event_history_len(&self, events: usize)
+    {
+        self.events.lock().unwrap().set_capacity(events);
+    }
+
+    pub(crate) fn set_report_dir(&self, dir: PathBuf)
+    {
+        *self.report_dir.lock().unwrap() = dir;
+    }
+
+    pub(crate) fn report_dir(&self) -> PathBuf
+    {
+        self.report_dir.lock().unwrap().clone()
+    }
+
+    pub(crate) fn last_report(&self) -> Option<PathBuf>
+    {
+        self.last_report.lock().unwrap().clone()
+    }
+
+    /// Runs in the panic hook, before the stack unwinds. The panicking thread may still
+    /// hold any of the locks, so they are only tried and nothing is logged.
+    fn on_panic(&self, payload: &(dyn Any + Send), location: Option<&Location>)
+    {
+        let thread = std::thread::current();
+        let logger = Logger::get();
+
+        let report = CrashReport {
+            time: chrono::Local::now(),
+            engine_version: ENGINE_VERSION.into(),
+            thread: thread.name().unwrap_or("unnamed").into(),
+            message: panic_message(payload),
+            location: location.map(|l| l.to_string()),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            device: self.device.try_lock().ok().and_then(|d| d.clone()),
+            log: logger.and_then(|l| l.buffer().try_lines(LogFormat::Detailed)).unwrap_or_default(),
+            events: self.events.try_lock()
+                .map(|events| events.entries().iter().map(|(elapsed, event)| event.format(*elapsed)).collect())
+                .unwrap_or_default(),
+        };
+
+        // Log files should contain everything up to the panic
+        if let Some(logger) = logger
+        {
+            logger.try_flush();
+        }
+
+        let dir = self.report_dir.try_lock()
+            .map(|dir| dir.clone())
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_REPORT_DIR));
+
+        match report.write_to_dir(dir)
+        {
+            Ok(path) => {
+                eprintln!("Crash report written to {}", path.display());
+
+                if let Ok(mut last) = self.last_report.try_lock()
+                {
+                    *last = Some(path);
+                }
+            },
+
+            Err(e) => eprintln!("Failed to write the crash report: {e}"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_last_entries()
+    {
+        let mut history = History::new(2);
+
+        for i in 0..3
+        {
+            history.push(i.to_string());
+        }
+
+        assert_eq!(history.entries(), ["1", "2"]);
+
+        history.set_capacity(1);
+        assert_eq!(history.entries(), ["2"]);
+
+        history.set_capacity(0);
+        history.push("3".into());
+        assert!(history.entries().is_empty());
+    }
+
+    #[test]
+    fn panic_messages()
+    {
+        let message = |payload: Box<dyn Any + Send>| panic_message(&*payload);
+
+        assert_eq!(message(Box::new("static")), "static");
+        assert_eq!(message(Box::new(String::from("formatted 1"))), "formatted 1");
+        assert_eq!(message(Box::new(1)), "Box<dyn Any>");
+    }
+
+    #[test]
+    fn report_format()
+    {
+        let report = CrashReport {
+            time: chrono::Local::now(),
+            engine_version: "0.1.0".into(),
+            thread: "v39 main loop".into(),
+            message: "index out of bounds".into(),
+            location: Some("src/main.rs:10:5".into()),
+            backtrace: "0: main\n1: start".into(),
+            device: None,
+            log: vec![],
+            events: vec!["[0.016s] KeyDown(Some((A, NONE)))".into()],
+        };
+
+        let mut out = vec![];
+        report.write(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.contains("Panic: index out of bounds\nLocation: src/main.rs:10:5\n"));
+        assert!(text.contains("\nDevice\n------\nNo device was selected\n"));
+        assert!(text.contains("\nBacktrace\n---------\n0: main\n1: start\n"));
+        assert!(text.contains("\nLast events\n-----------\n[0.016s] KeyDown(Some((A, NONE)))\n"));
+        assert!(text.ends_with("\nLast log lines\n--------------\nNone\n"));
+    }
+
+    #[test]
+    fn recorded_event_format()
+    {
+        let key = RecordedEvent::Engine(EngineEvent::WindowFocus, WindowId::MAIN);
+        let user = RecordedEvent::User(Event::new(7u32, vec![]));
+
+        assert_eq!(key.format(0.016), format!("[0.016s] WindowFocus in {:?}", WindowId::MAIN));
+        assert_eq!(user.format(1.5), "[1.500s] Event { id: 7, data: [] }");
+    }
+}
@@ -1,4 +1,9 @@
 use thiserror::Error;
+use std::path::PathBuf;
+
+
+/// Source errors of other crates which are kept as they are.
+pub type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 
 #[derive(Error, Debug)]
@@ -10,9 +15,185 @@ pub enum V39Error
     #[error("{0}")]
     NoSuitableDevie(String),
 
-    #[error("{0}")]
+    #[error(transparent)]
     VulkanError(#[from] vulkanalia::vk::ErrorCode),
 
     #[error("{0}")]
     Renderer(String),
+
+    #[error("Invalid shortcut {0:?}")]
+    InvalidShortcut(String),
+
+    #[error("Invalid window icon: {0}")]
+    InvalidWindowIcon(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Another crate installed its logger for the `log` crate before the engine did.
+    #[error("Another logger is already installed for the log crate")]
+    ForeignLogger,
+
+    #[error("Panicked: {0}")]
+    Panic(String),
+
+    #[error("Event loop error")]
+    EventLoop(#[from] winit::error::EventLoopError),
+
+    #[error("Window creation failed")]
+    Window(#[from] winit::error::OsError),
+
+    /// A dynamic library or the functions in it could not be loaded.
+    #[error("Failed to load {what}")]
+    Loader {what: String, #[source] source: BoxedError},
+
+    #[error("Invalid config: {message}")]
+    Config {message: String, #[source] source: Option<BoxedError>},
+
+    #[error("Failed to load asset {path:?}")]
+    Asset {path: PathBuf, #[source] source: BoxedError},
+
+    /// Describes what was being done when `source` occurred, see `ErrorContext`.
+    #[error("{context}")]
+    Context {context: String, #[source] source: Box<V39Error>},
+}
+
+
+impl V39Error
+{
+    pub fn loader(what: impl Into<String>, source: impl Into<BoxedError>) -> Self
+    {
+        V39Error::Loader {what: what.into(), source: source.into()}
+    }
+
+    pub fn config(message: impl Into<String>) -> Self
+    {
+        V39Error::Config {message: message.into(), source: None}
+    }
+
+    pub fn config_with(message: impl Into<String>, source: impl Into<BoxedError>) -> Self
+    {
+        V39Error::Config {message: message.into(), source: Some(source.into())}
+    }
+
+    pub fn asset(path: impl Into<PathBuf>, source: impl Into<BoxedError>) -> Self
+    {
+        V39Error::Asset {path: path.into(), source: source.into()}
+    }
+
+    pub fn context(self, context: impl Into<String>) -> Self
+    {
+        V39Error::Context {context: context.into(), source: Box::new(self)}
+    }
+
+    /// This error followed by its sources, each one only displays its own message.
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn std::error::Error + 'static)>
+    {
+        let first: &(dyn std::error::Error + 'static) = self;
+        std::iter::successors(Some(first), |e| e.source())
+    }
+
+    /// The messages of the whole chain joined by ": ", e.g. for logs.
+    pub fn report(&self) -> String
+    {
+        self.chain()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(": ")
+    }
+
+    /// The error at the end of the chain, after all contexts and wrappers.
+    pub fn root_cause(&self) -> &(dyn std::error::Error + 'static)
+    {
+        self.chain().last().unwrap_or(self)
+    }
+
+    /// The contexts from the outermost to the innermost.
+    pub fn contexts(&self) -> Vec<&str>
+    {
+        let mut contexts = vec![];
+        let mut error = self;
+
+        while let V39Error::Context {context, source} = error
+        {
+            contexts.push(context.as_str());
+            error = source;
+        }
+
+        contexts
+    }
+}
+
+
+/// Adds context to errors on their way up, e.g. `window.build().context("Creating the main window")?`.
+pub trait ErrorContext<T>
+{
+    fn context(self, context: impl Into<String>) -> Result<T, V39Error>;
+
+    /// Like `context`, but the message is only built on errors.
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, V39Error>;
+}
+
+
+impl<T, E: Into<V39Error>> ErrorContext<T> for Result<T, E>
+{
+    fn context(self, context: impl Into<String>) -> Result<T, V39Error>
+    {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> Result<T, V39Error>
+    {
+        self.map_err(|e| e.into().context(context()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn open() -> Result<(), V39Error>
+    {
+        Err(V39Error::config("missing field \"size\""))
+            .context("Reading level.ron")
+            .context("Loading the level")
+    }
+
+    #[test]
+    fn context_chain()
+    {
+        let error = open().unwrap_err();
+
+        assert_eq!(error.to_string(), "Loading the level");
+        assert_eq!(error.report(), "Loading the level: Reading level.ron: Invalid config: missing field \"size\"");
+        assert_eq!(error.contexts(), ["Loading the level", "Reading level.ron"]);
+        assert_eq!(error.chain().count(), 3);
+        assert_eq!(error.root_cause().to_string(), "Invalid config: missing field \"size\"");
+    }
+
+    #[test]
+    fn sources_are_kept()
+    {
+        let parse = "x".parse::<u32>().unwrap_err();
+        let error = V39Error::asset("sprites/player.png", parse.clone());
+
+        assert_eq!(error.to_string(), "Failed to load asset \"sprites/player.png\"");
+        assert_eq!(error.chain().nth(1).unwrap().to_string(), parse.to_string());
+        assert_eq!(error.report(), format!("Failed to load asset \"sprites/player.png\": {parse}"));
+        assert_eq!(V39Error::config("bad").chain().count(), 1);
+    }
+
+    #[test]
+    fn from_sources_are_reported_once()
+    {
+        let io: V39Error = std::io::Error::other("boom").into();
+        assert_eq!(io.report(), "boom");
+        assert_eq!(io.context("Reading level.ron").report(), "Reading level.ron: boom");
+
+        let event_loop: V39Error = winit::error::EventLoopError::AlreadyRunning.into();
+        assert_eq!(event_loop.to_string(), "Event loop error");
+        assert_eq!(event_loop.report(), "Event loop error: EventLoop is already running");
+    }
 }
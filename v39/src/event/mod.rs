@@ -6,19 +6,33 @@ pub mod event;
 use once_cell::sync::OnceCell;
 use std::sync::Mutex;
 use crate::prelude::*;
+use crate::window::WindowId;
+use crate::timer::schedule::TimerId;
+use crate::tween::TweenId;
+use crate::crash::{CrashHandler, RecordedEvent};
 
 
 static INSTANCE: OnceCell<EventHandler> = OnceCell::new();
 
 
+/// A receiver and whether it belongs to the engine and sees all input regardless of the context stack.
+/// The flag can not be set from outside of the crate, so no receiver can opt out of its contexts.
+pub(crate) struct Registered
+{
+    pub(crate) receiver: Box<dyn EventReceiver + Sync + Send>,
+    pub(crate) raw: bool,
+}
+
+
 pub(crate) struct EventHandler
 {
-    engine_events: Mutex<Vec<EngineEvent>>,
+    engine_events: Mutex<Vec<(EngineEvent, WindowId)>>,
     events: Mutex<Vec<Event>>,
-    receiver: Mutex<Vec<Box<dyn EventReceiver + Sync + Send>>>,
-    locked_engine_events: Mutex<Vec<EngineEvent>>,
+    receiver: Mutex<Vec<Registered>>,
+    locked_engine_events: Mutex<Vec<(EngineEvent, WindowId)>>,
     locked_events: Mutex<Vec<Event>>,
-    locked_receiver: Mutex<Vec<Box<dyn EventReceiver + Sync + Send>>>,
+    locked_receiver: Mutex<Vec<Registered>>,
+    current_window: Mutex<WindowId>,
 }
 
 
@@ -31,18 +45,21 @@ impl EventHandler
             return Err(V39Error::Reinit("EventHandler".into()));
         }
 
-        let handler = EventHandler {
+        INSTANCE.set(Self::new());
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    fn new() -> Self
+    {
+        EventHandler {
             engine_events: Mutex::new(vec![]),
             events: Mutex::new(vec![]),
             receiver: Mutex::new(vec![]),
             locked_engine_events: Mutex::new(vec![]),
             locked_events: Mutex::new(vec![]),
             locked_receiver: Mutex::new(vec![]),
-        };
-
-        
-        INSTANCE.set(handler);
-        Ok(INSTANCE.get().unwrap())
+            current_window: Mutex::new(WindowId::MAIN),
+        }
     }
 
     pub(crate) fn get() -> &'static Self
@@ -64,7 +81,7 @@ impl EventHandler
         {
             for e in eevents.drain(..).collect::<Vec<_>>()
             {
-                if filter(&e) {locked_eevents.push(e)}
+                if filter(&e.0) {locked_eevents.push(e)}
                 else {eevents.push(e)}
             }
         }
@@ -79,14 +96,27 @@ impl EventHandler
         }
     }
 
-    pub(crate) fn record_engine_event(&self, event: EngineEvent)
+    pub(crate) fn record_engine_event(&self, event: EngineEvent, window: WindowId)
     {
         if let Ok(mut eevents) = self.engine_events.lock()
         {
-            eevents.push(event);
+            eevents.push((event, window));
         }
     }
 
+    pub(crate) fn set_current_window(&self, window: WindowId)
+    {
+        if let Ok(mut current) = self.current_window.lock()
+        {
+            *current = window;
+        }
+    }
+
+    pub(crate) fn current_window(&self) -> WindowId
+    {
+        *self.current_window.lock().unwrap()
+    }
+
     pub(crate) fn snapchot_receiver_queue(&self)
     {
         if let (Ok(mut receivers), Ok(mut locked_receivers)) = (self.receiver.lock(), self.locked_receiver.lock())
@@ -103,11 +133,11 @@ impl EventHandler
         }
     }
 
-    pub(crate) fn record_receiver(&self, rec: Box<dyn EventReceiver + Sync + Send>)
+    pub(crate) fn record_receiver(&self, receiver: Box<dyn EventReceiver + Sync + Send>, raw: bool)
     {
         if let Ok(mut receivers) = self.receiver.lock()
         {
-            receivers.push(rec);
+            receivers.push(Registered {receiver, raw});
         }
     }
 
@@ -121,7 +151,7 @@ impl EventHandler
         None
     }
 
-    pub(crate) fn fetch_engine_event(&self) -> Option<EngineEvent>
+    pub(crate) fn fetch_engine_event(&self) -> Option<(EngineEvent, WindowId)>
     {
         if let Ok(mut eevents) = self.locked_engine_events.lock()
         {
@@ -142,7 +172,7 @@ impl EventHandler
         vec![]
     }
 
-    pub(crate) fn fetch_engine_event_snapshots(&self) -> Vec<EngineEvent>
+    pub(crate) fn fetch_engine_event_snapshots(&self) -> Vec<(EngineEvent, WindowId)>
     {
         
         if let Ok(mut eevents) = self.locked_engine_events.lock()
@@ -153,15 +183,35 @@ impl EventHandler
         vec![]
     }
 
-    pub(crate) fn foreach_receiver_snapshot(&self, mut f: impl FnMut(&mut Box<dyn EventReceiver + Send + Sync + 'static>) -> V39Result<()>)
+    /// Hands every queued engine event which passes the filter to all receivers.
+    /// Events are dispatched in the order they were queued, each one to every receiver before the next.
+    pub(crate) fn dispatch_engine_events(&self, filter: impl Fn(&EngineEvent) -> bool, mut f: impl FnMut(&EngineEvent, &mut Registered) -> V39Result<()>)
+    {
+        self.snapchot_receiver_queue();
+        self.snapshot_engine_event_queue(filter);
+
+        for (event, window) in self.fetch_engine_event_snapshots()
+        {
+            remember(&event, window);
+            self.set_current_window(window);
+            self.foreach_receiver_snapshot(|rec| f(&event, rec));
+        }
+
+        self.set_current_window(WindowId::MAIN);
+        self.apply_receiver_snapshot();
+    }
+
+    pub(crate) fn foreach_receiver_snapshot(&self, mut f: impl FnMut(&mut Registered) -> V39Result<()>)
     {
         if let Ok(mut recs) = self.locked_receiver.lock()
         {
             for rec in &mut *recs
             {
+                crate::profile_scope!(rec.receiver.receiver_name());
+
                 if let Err(e) = f(rec)
                 {
-                    error!("Error while dispatching events: {e}");
+                    error!("Error while dispatching events: {}", e.report());
                 }
             }
         }
@@ -169,20 +219,61 @@ impl EventHandler
 }
 
 
+/// Keeps the event for crash reports, the ones fired every frame would crowd out the rest.
+pub(crate) fn remember(event: &EngineEvent, window: WindowId)
+{
+    if matches!(event, EngineEvent::FrameBegin | EngineEvent::FrameEnd | EngineEvent::Tick(_) | EngineEvent::FixedTick(_))
+    {
+        return;
+    }
+
+    if let Some(crash) = CrashHandler::get()
+    {
+        crash.record_event(RecordedEvent::Engine(event.clone(), window));
+    }
+}
+
+
 #[derive(Debug, Clone)]
 pub(crate) enum EngineEvent
 {
     Reset, FrameBegin, FrameEnd,
-    KeyDown(Option<input::V39Key>),
-    KeyUp(Option<input::V39Key>),
-    Tick(Option<f32>),
+    KeyDown(Option<(input::V39Key, input::Modifiers)>),
+    KeyUp(Option<(input::V39Key, input::Modifiers)>),
+    KeyRepeat(Option<(input::V39Key, input::Modifiers)>),
+    ModifiersChanged(Option<input::Modifiers>),
+    MouseDown(Option<input::V39Mouse>),
+    MouseUp(Option<input::V39Mouse>),
+    MouseMove(Option<(f64, f64)>),
+    MouseMotion(Option<(f64, f64)>),
+    PadDown(Option<input::V39Pad>),
+    PadUp(Option<input::V39Pad>),
+    PadAxis(Option<(input::V39Axis, (f32, f32))>),
+    Touch(Option<input::Touch>),
+    TouchGesture(Option<input::TouchGesture>),
+    /// Real and game delta of the frame.
+    Tick(Option<(f32, f32)>),
     FixedTick(Option<f32>),
-    Quit(Option<u32>),
+    TimerFired(Option<TimerId>),
+    TweenFinished(Option<TweenId>),
+    QuitRequested(Option<QuitReason>),
+    Quit(Option<QuitReason>),
     WindowClose,
+    WindowOpened(Option<WindowId>),
+    WindowClosed(Option<WindowId>),
     WindowMinimize,
+    WindowRestore,
     WindowFocus,
     WindowUnfocus,
     WindowResize(Option<(u32, u32)>),
+    WindowMoved(Option<(i32, i32)>),
+    WindowOccluded(Option<bool>),
+    ScaleFactorChanged(Option<f64>),
+    ThemeChanged(Option<crate::window::Theme>),
+    FileHovered(Option<std::path::PathBuf>),
+    FileHoverCancelled,
+    FileDropped(Option<std::path::PathBuf>),
+    RedrawRequested,
 }
 
 
@@ -192,4 +283,98 @@ impl EngineEvent
     {
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
+
+    /// Events produced by input devices, they are dispatched together in the order they arrived.
+    pub(crate) fn is_input(&self) -> bool
+    {
+        matches!(self,
+            EngineEvent::KeyDown(_) | EngineEvent::KeyUp(_) | EngineEvent::KeyRepeat(_) | EngineEvent::ModifiersChanged(_) |
+            EngineEvent::MouseDown(_) | EngineEvent::MouseUp(_) | EngineEvent::MouseMove(_) | EngineEvent::MouseMotion(_) |
+            EngineEvent::PadDown(_) | EngineEvent::PadUp(_) | EngineEvent::PadAxis(_) | EngineEvent::Touch(_))
+    }
+
+    /// The variant name without its payload.
+    pub(crate) fn kind_name(&self) -> String
+    {
+        let name = format!("{self:?}");
+
+        match name.split_once('(')
+        {
+            Some((kind, _)) => kind.to_string(),
+            None => name,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::input::{V39Key, V39Mouse, Modifiers};
+    use crate::input::state::ButtonState;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct KeyTracker(Arc<Mutex<ButtonState<V39Key>>>);
+
+    impl EventReceiver for KeyTracker
+    {
+        fn key_down(&mut self, key: V39Key, _: Modifiers) -> V39Result<()>
+        {
+            self.0.lock().unwrap().press(key, Duration::ZERO);
+            Ok(())
+        }
+
+        fn key_up(&mut self, key: V39Key, _: Modifiers) -> V39Result<()>
+        {
+            self.0.lock().unwrap().release(key);
+            Ok(())
+        }
+    }
+
+    fn dispatch_input(handler: &EventHandler)
+    {
+        handler.dispatch_engine_events(EngineEvent::is_input, |event, rec| match *event
+        {
+            EngineEvent::KeyDown(Some((key, mods))) => rec.receiver.key_down(key, mods),
+            EngineEvent::KeyUp(Some((key, mods))) => rec.receiver.key_up(key, mods),
+            _ => Ok(()),
+        });
+    }
+
+    #[test]
+    fn key_released_and_pressed_within_one_frame_stays_held()
+    {
+        let handler = EventHandler::new();
+        let keys = Arc::new(Mutex::new(ButtonState::default()));
+        handler.record_receiver(Box::new(KeyTracker(keys.clone())), false);
+
+        handler.record_engine_event(EngineEvent::KeyDown(Some((V39Key::D, Modifiers::NONE))), WindowId::MAIN);
+        dispatch_input(&handler);
+        keys.lock().unwrap().begin_frame();
+
+        handler.record_engine_event(EngineEvent::KeyUp(Some((V39Key::D, Modifiers::NONE))), WindowId::MAIN);
+        handler.record_engine_event(EngineEvent::MouseDown(Some(V39Mouse::Left)), WindowId::MAIN);
+        handler.record_engine_event(EngineEvent::KeyDown(Some((V39Key::D, Modifiers::NONE))), WindowId::MAIN);
+        dispatch_input(&handler);
+
+        let keys = keys.lock().unwrap();
+        assert!(keys.is_up(V39Key::D));
+        assert!(keys.is_down(V39Key::D));
+        assert!(keys.is_held(V39Key::D));
+    }
+
+    #[test]
+    fn only_input_events_are_taken()
+    {
+        let handler = EventHandler::new();
+        handler.record_engine_event(EngineEvent::KeyDown(Some((V39Key::A, Modifiers::NONE))), WindowId::MAIN);
+        handler.record_engine_event(EngineEvent::WindowFocus, WindowId::MAIN);
+        dispatch_input(&handler);
+
+        let queued = handler.engine_events.lock().unwrap();
+        assert_eq!(queued.len(), 1);
+        assert!(queued[0].0.var_eq(&EngineEvent::WindowFocus));
+    }
 }
@@ -1,8 +1,33 @@
 use crate::prelude::*;
+use crate::timer::Clock;
+use crate::timer::schedule::TimerId;
+use crate::tween::TweenId;
 
 
 pub trait EventReceiver
 {
+    /// The input context this receiver belongs to.
+    /// Input consumed by a context above it is not delivered.
+    /// `None` places the receiver on the base layer below every context.
+    fn input_context(&self) -> Option<&str>
+    {
+        None
+    }
+
+    /// The clock `tick` deltas are taken from.
+    /// Gameplay follows game time by default, menus and other UI which keep running
+    /// while the game is paused should use real time.
+    fn clock(&self) -> Clock
+    {
+        Clock::Game
+    }
+
+    /// Name of the receiver in profiles, the type name by default.
+    fn receiver_name(&self) -> &'static str
+    {
+        std::any::type_name::<Self>()
+    }
+
     fn dispatch_event(&mut self, event: Event) -> V39Result<()>
     {
         Ok(())
@@ -13,12 +38,73 @@ pub trait EventReceiver
         Ok(())
     }
 
-    fn key_down(&mut self, key: input::V39Key) -> V39Result<()>
+    fn key_down(&mut self, key: input::V39Key, mods: input::Modifiers) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn key_up(&mut self, key: input::V39Key, mods: input::Modifiers) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// Only fires for keys with a repeat mode other than `KeyRepeat::Off`,
+    /// see `InputManagerInterface::set_key_repeat` and `InputContext::repeat`.
+    fn key_repeat(&mut self, key: input::V39Key, mods: input::Modifiers) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn modifiers_changed(&mut self, mods: input::Modifiers) -> V39Result<()>
     {
         Ok(())
     }
 
-    fn key_up(&mut self, key: input::V39Key) -> V39Result<()>
+    fn mouse_down(&mut self, button: input::V39Mouse) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn mouse_up(&mut self, button: input::V39Mouse) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// Position within the window the cursor moved in, see `EventHandlerInterface::event_window`.
+    fn mouse_move(&mut self, position: (f64, f64)) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// Raw mouse movement, independent of the cursor and not affected by acceleration.
+    /// Keeps being reported while the cursor is locked.
+    fn mouse_motion(&mut self, delta: (f64, f64)) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn pad_down(&mut self, button: input::V39Pad) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn pad_up(&mut self, button: input::V39Pad) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn pad_axis(&mut self, axis: input::V39Axis, value: (f32, f32)) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// Fires for every change of a finger on the screen, see `InputManagerInterface::touches`.
+    fn touch(&mut self, touch: input::Touch) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn touch_gesture(&mut self, gesture: input::TouchGesture) -> V39Result<()>
     {
         Ok(())
     }
@@ -33,21 +119,66 @@ pub trait EventReceiver
         Ok(())
     }
     
+    /// Called once per frame with the frame time in seconds as measured by `clock`.
     fn tick(&mut self, delta: f32) -> V39Result<()>
     {
         Ok(())
     }
 
-    fn quit(&mut self, reason: u32) -> V39Result<()>
+    /// Called zero or more times per frame after `tick`, always with the same delta.
+    /// Fixed ticks follow game time regardless of `clock`, they stop while the game is paused.
+    /// See `TimerInterface::set_fixed_step` and `TimerInterface::fixed_alpha`.
+    fn fixed_tick(&mut self, delta: f32) -> V39Result<()>
     {
         Ok(())
     }
 
+    /// A timer started with `TimerInterface::schedule` fired.
+    /// Every receiver is told about every timer, ignore the ids you did not start.
+    fn timer_fired(&mut self, id: TimerId) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// An animation started with `TweenInterface::play` finished,
+    /// its final values can still be read during this frame.
+    fn tween_finished(&mut self, id: TweenId) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// Someone called `App::quit_with`, call `App::cancel_quit` to keep running,
+    /// e.g. to ask about unsaved changes first.
+    fn quit_requested(&mut self, reason: &QuitReason) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// The app quits after this frame, no receiver vetoed it or it could not be vetoed.
+    /// The renderer is still alive, it is torn down once every receiver was told.
+    fn quit(&mut self, reason: &QuitReason) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// The user asked to close the main window.
+    /// Other windows close right away and report `window_closed` instead.
     fn window_close(&mut self) -> V39Result<()>
     {
         Ok(())
     }
 
+    /// A window opened with `App::open_window` is ready to use.
+    fn window_opened(&mut self, window: crate::window::WindowId) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn window_closed(&mut self, window: crate::window::WindowId) -> V39Result<()>
+    {
+        Ok(())
+    }
+
     fn window_resize(&mut self, size: (u32, u32)) -> V39Result<()>
     {
         Ok(())
@@ -58,9 +189,70 @@ pub trait EventReceiver
         Ok(())
     }
 
+    /// Everything held is released, `key_up`, `mouse_up` and `pad_up` follow in the next frame.
     fn window_unfocus(&mut self) -> V39Result<()>
     {
         Ok(())
     }
 
+    fn window_minimize(&mut self) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// The window is no longer minimized.
+    fn window_restore(&mut self) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// New position of the top left corner of the window in physical pixels.
+    fn window_moved(&mut self, position: (i32, i32)) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// Whether the window is fully hidden, e.g. behind another window or on another workspace.
+    /// Not every platform reports occlusion.
+    fn window_occluded(&mut self, occluded: bool) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// The window moved to a monitor with a different DPI or the DPI setting changed.
+    fn scale_factor_changed(&mut self, scale_factor: f64) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    fn theme_changed(&mut self, theme: crate::window::Theme) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// A file is dragged over the window, fires once per file.
+    fn file_hovered(&mut self, path: std::path::PathBuf) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// The hovered files left the window without being dropped.
+    fn file_hover_cancelled(&mut self) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// A file was dropped onto the window, fires once per file.
+    fn file_dropped(&mut self, path: std::path::PathBuf) -> V39Result<()>
+    {
+        Ok(())
+    }
+
+    /// The window system asked for the window to be redrawn.
+    /// Not called while the window is minimized or occluded, see `RendererInterface::is_window_paused`.
+    fn redraw_requested(&mut self) -> V39Result<()>
+    {
+        Ok(())
+    }
+
 }
@@ -0,0 +1,429 @@
+use crate::input::{V39Key, V39Axis};
+use crate::interfaces::input_manager::InputManagerInterface;
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Deadzone
+{
+    None,
+    Axial(f32),     // Applied to every component on its own, keeps digital feeling cardinal directions
+    Radial(f32),    // Applied to the length of the stick vector, keeps the direction intact
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ResponseCurve
+{
+    Linear,
+    Quadratic,
+    Cubic,
+    Power(f32),
+}
+
+
+impl ResponseCurve
+{
+    pub fn apply(&self, value: f32) -> f32
+    {
+        let magnitude = value.abs();
+
+        let shaped = match self
+        {
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Quadratic => magnitude * magnitude,
+            ResponseCurve::Cubic => magnitude * magnitude * magnitude,
+            ResponseCurve::Power(exp) => magnitude.powf(*exp),
+        };
+
+        shaped.copysign(value)
+    }
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct AxisSettings
+{
+    pub deadzone: Deadzone,
+    pub outer_deadzone: f32,
+    pub curve: ResponseCurve,
+    pub sensitivity: f32,
+
+    /// On 1D axes `invert_x` applies to keys and X components, `invert_y` to Y components.
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+
+impl Default for AxisSettings
+{
+    fn default() -> Self
+    {
+        Self {
+            deadzone: Deadzone::Radial(0.15),
+            outer_deadzone: 0.95,
+            curve: ResponseCurve::Linear,
+            sensitivity: 1.0,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+}
+
+
+impl AxisSettings
+{
+    /// Runs a raw stick value through the deadzones and the response curve
+    /// and returns a vector with a length of at most 1.
+    pub fn process(&self, raw: (f32, f32)) -> (f32, f32)
+    {
+        let (x, y) = match self.deadzone
+        {
+            Deadzone::None => (
+                self.shape(rescale(raw.0.abs(), 0.0, self.outer_deadzone)).copysign(raw.0),
+                self.shape(rescale(raw.1.abs(), 0.0, self.outer_deadzone)).copysign(raw.1),
+            ),
+
+            Deadzone::Axial(inner) => (
+                self.shape(rescale(raw.0.abs(), inner, self.outer_deadzone)).copysign(raw.0),
+                self.shape(rescale(raw.1.abs(), inner, self.outer_deadzone)).copysign(raw.1),
+            ),
+
+            Deadzone::Radial(inner) => {
+                let length = (raw.0 * raw.0 + raw.1 * raw.1).sqrt();
+
+                if length <= inner || length == 0.0 {(0.0, 0.0)}
+
+                else
+                {
+                    let scale = self.shape(rescale(length, inner, self.outer_deadzone)) / length;
+                    (raw.0 * scale, raw.1 * scale)
+                }
+            },
+        };
+
+        self.finish((x, y))
+    }
+
+    /// Applies sensitivity and inversion to an already normalized value,
+    /// this is used for digital sources which have no use for deadzones.
+    pub fn finish(&self, value: (f32, f32)) -> (f32, f32)
+    {
+        let (x, y) = clamp_length((value.0 * self.sensitivity, value.1 * self.sensitivity));
+
+        (
+            if self.invert_x {-x} else {x},
+            if self.invert_y {-y} else {y},
+        )
+    }
+
+    fn shape(&self, value: f32) -> f32
+    {
+        self.curve.apply(value)
+    }
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AxisComponent
+{
+    X, Y,
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Axis1DSource
+{
+    Keys {negative: V39Key, positive: V39Key},
+    Pad(V39Axis, AxisComponent),
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Axis2DSource
+{
+    Keys {left: V39Key, right: V39Key, down: V39Key, up: V39Key},
+    Pad(V39Axis),
+}
+
+
+/// A one dimensional axis in -1..=1 which is fed by any number of sources.
+/// The source with the largest magnitude wins.
+#[derive(Clone, Debug, Default)]
+pub struct Axis1D
+{
+    pub sources: Vec<Axis1DSource>,
+    pub settings: AxisSettings,
+}
+
+
+impl Axis1D
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn with_keys(mut self, negative: V39Key, positive: V39Key) -> Self
+    {
+        self.sources.push(Axis1DSource::Keys {negative, positive});
+        self
+    }
+
+    pub fn with_pad(mut self, axis: V39Axis, component: AxisComponent) -> Self
+    {
+        self.sources.push(Axis1DSource::Pad(axis, component));
+        self
+    }
+
+    pub fn with_settings(mut self, settings: AxisSettings) -> Self
+    {
+        self.settings = settings;
+        self
+    }
+
+    pub fn value(&self, input: &InputManagerInterface) -> f32
+    {
+        let mut result = 0.0f32;
+
+        for source in &self.sources
+        {
+            let value = match *source
+            {
+                Axis1DSource::Keys {negative, positive} => {
+                    let value = key_value(input, positive) - key_value(input, negative);
+                    self.settings.finish((value, 0.0)).0
+                },
+
+                Axis1DSource::Pad(axis, component) => pad_component(&self.settings, input.axis_raw(axis), component),
+            };
+
+            if value.abs() > result.abs()
+            {
+                result = value;
+            }
+        }
+
+        result
+    }
+}
+
+
+/// A two dimensional axis, e.g. a movement vector, fed by any number of sources.
+/// Keyboard and pad sources produce the same normalized range so
+/// diagonal keyboard movement is not faster than moving along one axis.
+#[derive(Clone, Debug, Default)]
+pub struct Axis2D
+{
+    pub sources: Vec<Axis2DSource>,
+    pub settings: AxisSettings,
+}
+
+
+impl Axis2D
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Shorthand for the usual W/A/S/D movement keys.
+    pub fn wasd() -> Self
+    {
+        Self::new().with_keys(V39Key::A, V39Key::D, V39Key::S, V39Key::W)
+    }
+
+    pub fn with_keys(mut self, left: V39Key, right: V39Key, down: V39Key, up: V39Key) -> Self
+    {
+        self.sources.push(Axis2DSource::Keys {left, right, down, up});
+        self
+    }
+
+    pub fn with_pad(mut self, axis: V39Axis) -> Self
+    {
+        self.sources.push(Axis2DSource::Pad(axis));
+        self
+    }
+
+    pub fn with_settings(mut self, settings: AxisSettings) -> Self
+    {
+        self.settings = settings;
+        self
+    }
+
+    pub fn value(&self, input: &InputManagerInterface) -> (f32, f32)
+    {
+        let mut result = (0.0f32, 0.0f32);
+
+        for source in &self.sources
+        {
+            let value = match *source
+            {
+                Axis2DSource::Keys {left, right, down, up} => {
+                    let x = key_value(input, right) - key_value(input, left);
+                    let y = key_value(input, up) - key_value(input, down);
+                    self.settings.finish(clamp_length((x, y)))
+                },
+
+                Axis2DSource::Pad(axis) => self.settings.process(input.axis_raw(axis)),
+            };
+
+            if length(value) > length(result)
+            {
+                result = value;
+            }
+        }
+
+        result
+    }
+}
+
+
+fn key_value(input: &InputManagerInterface, key: V39Key) -> f32
+{
+    if input.is_held(key) {1.0}
+    else {0.0}
+}
+
+
+/// Processes a single stick component, keeping it in its place so the matching invert flag applies.
+fn pad_component(settings: &AxisSettings, raw: (f32, f32), component: AxisComponent) -> f32
+{
+    let settings = AxisSettings {
+        deadzone: one_dimensional(settings.deadzone),
+        ..*settings
+    };
+
+    match component
+    {
+        AxisComponent::X => settings.process((raw.0, 0.0)).0,
+        AxisComponent::Y => settings.process((0.0, raw.1)).1,
+    }
+}
+
+
+fn one_dimensional(deadzone: Deadzone) -> Deadzone
+{
+    // A radial deadzone over a single component is just an axial one
+    match deadzone
+    {
+        Deadzone::Radial(inner) => Deadzone::Axial(inner),
+        other => other,
+    }
+}
+
+
+fn rescale(value: f32, inner: f32, outer: f32) -> f32
+{
+    if value <= inner {return 0.0}
+    if outer <= inner {return 1.0}
+
+    ((value - inner) / (outer - inner)).min(1.0)
+}
+
+
+fn length(value: (f32, f32)) -> f32
+{
+    (value.0 * value.0 + value.1 * value.1).sqrt()
+}
+
+
+fn clamp_length(value: (f32, f32)) -> (f32, f32)
+{
+    let len = length(value);
+
+    if len > 1.0 {(value.0 / len, value.1 / len)}
+    else {value}
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn settings(deadzone: Deadzone, curve: ResponseCurve) -> AxisSettings
+    {
+        AxisSettings {deadzone, outer_deadzone: 1.0, curve, ..AxisSettings::default()}
+    }
+
+    fn assert_near(actual: (f32, f32), expected: (f32, f32))
+    {
+        assert!((actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn radial_deadzone_keeps_the_direction()
+    {
+        let radial = settings(Deadzone::Radial(0.2), ResponseCurve::Linear);
+
+        assert_eq!(radial.process((0.1, 0.1)), (0.0, 0.0));
+
+        let (x, y) = radial.process((0.5, 0.1));
+        assert!(y > 0.0);
+        assert!((x / y - 5.0).abs() < 1e-4);
+        assert!((length((x, y)) - rescale(length((0.5, 0.1)), 0.2, 1.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn axial_deadzone_snaps_components()
+    {
+        let axial = settings(Deadzone::Axial(0.2), ResponseCurve::Linear);
+
+        assert_eq!(axial.process((0.1, 0.1)), (0.0, 0.0));
+        assert_near(axial.process((0.5, 0.1)), (0.375, 0.0));
+        assert_near(axial.process((-0.6, 0.6)), (-0.5, 0.5));
+    }
+
+    #[test]
+    fn outer_deadzone_reaches_full_deflection()
+    {
+        let default = AxisSettings::default();
+
+        assert_near(default.process((0.96, 0.0)), (1.0, 0.0));
+        assert_near(default.process((0.0, -0.97)), (0.0, -1.0));
+
+        let axial = AxisSettings {deadzone: Deadzone::Axial(0.15), ..default};
+        assert_near(axial.process((1.0, 0.0)), (1.0, 0.0));
+    }
+
+    #[test]
+    fn response_curves()
+    {
+        let value = |curve| settings(Deadzone::None, curve).process((0.5, -0.5));
+
+        assert_near(value(ResponseCurve::Linear), (0.5, -0.5));
+        assert_near(value(ResponseCurve::Quadratic), (0.25, -0.25));
+        assert_near(value(ResponseCurve::Cubic), (0.125, -0.125));
+        assert_near(value(ResponseCurve::Power(0.5)), (0.5f32.sqrt(), -0.5f32.sqrt()));
+    }
+
+    #[test]
+    fn diagonal_keys_are_not_faster()
+    {
+        let value = AxisSettings::default().finish(clamp_length((1.0, 1.0)));
+
+        assert!(length(value) <= 1.0 + 1e-6);
+        assert_near(value, (0.5f32.sqrt(), 0.5f32.sqrt()));
+    }
+
+    #[test]
+    fn sensitivity_is_clamped()
+    {
+        let fast = AxisSettings {sensitivity: 2.0, ..settings(Deadzone::None, ResponseCurve::Linear)};
+
+        assert_near(fast.process((0.25, 0.0)), (0.5, 0.0));
+        assert!(length(fast.process((0.8, 0.8))) <= 1.0 + 1e-6);
+    }
+
+    #[test]
+    fn one_dimensional_invert_follows_the_component()
+    {
+        let inverted_y = AxisSettings {invert_y: true, ..settings(Deadzone::None, ResponseCurve::Linear)};
+
+        assert_near((pad_component(&inverted_y, (0.5, 0.5), AxisComponent::Y), 0.0), (-0.5, 0.0));
+        assert_near((pad_component(&inverted_y, (0.5, 0.5), AxisComponent::X), 0.0), (0.5, 0.0));
+
+        let inverted_x = AxisSettings {invert_x: true, ..inverted_y};
+        assert_near((pad_component(&inverted_x, (0.5, 0.5), AxisComponent::X), 0.0), (-0.5, 0.0));
+    }
+}
@@ -0,0 +1,357 @@
+use crate::input::{V39Key, V39Mouse, V39Pad, Modifiers, Shortcut};
+use std::sync::{Arc, Mutex};
+
+
+/// The input a capture ended with.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CapturedInput
+{
+    /// A key with the modifiers held while pressing it.
+    /// A modifier released without pressing another key is captured on its own.
+    Key(Shortcut),
+    Mouse(V39Mouse),
+    Pad(V39Pad),
+}
+
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CaptureState
+{
+    Pending,
+    Captured(CapturedInput),
+
+    /// Cancelled by Esc, by `InputCapture::cancel` or by starting another capture.
+    Cancelled,
+}
+
+
+/// Handle to a running capture, see `InputManagerInterface::capture_next_input`.
+/// Poll it every frame until it is no longer pending.
+#[derive(Clone, Debug)]
+pub struct InputCapture
+{
+    state: Arc<Mutex<CaptureState>>,
+}
+
+
+impl InputCapture
+{
+    pub fn state(&self) -> CaptureState
+    {
+        *self.state.lock().unwrap()
+    }
+
+    pub fn is_pending(&self) -> bool
+    {
+        self.state() == CaptureState::Pending
+    }
+
+    /// The captured input, `None` while pending or after a cancel.
+    pub fn captured(&self) -> Option<CapturedInput>
+    {
+        match self.state()
+        {
+            CaptureState::Captured(input) => Some(input),
+            _ => None,
+        }
+    }
+
+    /// Stops the capture, has no effect once it finished.
+    pub fn cancel(&self)
+    {
+        self.finish(CaptureState::Cancelled);
+    }
+
+    fn finish(&self, result: CaptureState)
+    {
+        if let Ok(mut state) = self.state.lock()
+        {
+            if *state == CaptureState::Pending {*state = result}
+        }
+    }
+}
+
+
+/// Codes which have been taken by a capture and are hidden from everyone
+/// but the engine until they are released.
+struct Swallowed<T>
+{
+    codes: Vec<(T, bool)>,
+}
+
+
+impl<T> Default for Swallowed<T>
+{
+    fn default() -> Self
+    {
+        Self {codes: vec![]}
+    }
+}
+
+
+impl<T: PartialEq + Copy> Swallowed<T>
+{
+    fn insert(&mut self, code: T)
+    {
+        if !self.contains(code) {self.codes.push((code, false))}
+    }
+
+    /// The release is swallowed as well, so the code is only forgotten on the next frame.
+    fn release(&mut self, code: T)
+    {
+        for (c, released) in &mut self.codes
+        {
+            if *c == code {*released = true}
+        }
+    }
+
+    fn contains(&self, code: T) -> bool
+    {
+        self.codes.iter().any(|(c, _)| *c == code)
+    }
+
+    fn begin_frame(&mut self)
+    {
+        self.codes.retain(|(_, released)| !released);
+    }
+}
+
+
+#[derive(Default)]
+pub(crate) struct CaptureTracker
+{
+    active: Option<InputCapture>,
+    modifier: Option<V39Key>,
+    keys: Swallowed<V39Key>,
+    mouse: Swallowed<V39Mouse>,
+    pad: Swallowed<V39Pad>,
+}
+
+
+impl CaptureTracker
+{
+    pub(crate) fn start(&mut self) -> InputCapture
+    {
+        if let Some(previous) = self.active.take()
+        {
+            previous.cancel();
+        }
+
+        let capture = InputCapture {state: Arc::new(Mutex::new(CaptureState::Pending))};
+
+        self.modifier = None;
+        self.active = Some(capture.clone());
+        capture
+    }
+
+    /// The running capture, captures finished from the outside are dropped here.
+    fn active(&mut self) -> Option<&InputCapture>
+    {
+        if self.active.as_ref().is_some_and(|c| !c.is_pending())
+        {
+            self.active = None;
+            self.modifier = None;
+        }
+
+        self.active.as_ref()
+    }
+
+    fn finish(&mut self, result: CaptureState)
+    {
+        if let Some(capture) = self.active.take()
+        {
+            capture.finish(result);
+        }
+
+        self.modifier = None;
+    }
+
+    pub(crate) fn key_down(&mut self, key: V39Key, mods: Modifiers)
+    {
+        if self.active().is_none() {return}
+
+        self.keys.insert(key);
+
+        if key == V39Key::Esc
+        {
+            self.finish(CaptureState::Cancelled);
+        }
+
+        // Wait for the key the modifier belongs to
+        else if Modifiers::is_modifier(key)
+        {
+            self.modifier.get_or_insert(key);
+        }
+
+        else
+        {
+            let mut mods = mods;
+
+            if let Some(modifier) = self.modifier
+            {
+                mods |= Modifiers::from_key(modifier).unwrap_or_default();
+            }
+
+            self.finish(CaptureState::Captured(CapturedInput::Key(Shortcut::new(mods, key))));
+        }
+    }
+
+    pub(crate) fn key_up(&mut self, key: V39Key)
+    {
+        self.keys.release(key);
+
+        if self.active().is_none() {return}
+
+        if self.modifier == Some(key)
+        {
+            self.finish(CaptureState::Captured(CapturedInput::Key(Shortcut::new(Modifiers::NONE, key))));
+        }
+    }
+
+    pub(crate) fn mouse_down(&mut self, button: V39Mouse)
+    {
+        if self.active().is_none() {return}
+
+        self.mouse.insert(button);
+        self.finish(CaptureState::Captured(CapturedInput::Mouse(button)));
+    }
+
+    pub(crate) fn mouse_up(&mut self, button: V39Mouse)
+    {
+        self.mouse.release(button);
+    }
+
+    pub(crate) fn pad_down(&mut self, button: V39Pad)
+    {
+        if self.active().is_none() {return}
+
+        self.pad.insert(button);
+        self.finish(CaptureState::Captured(CapturedInput::Pad(button)));
+    }
+
+    pub(crate) fn pad_up(&mut self, button: V39Pad)
+    {
+        self.pad.release(button);
+    }
+
+    pub(crate) fn swallows_key(&self, key: V39Key) -> bool
+    {
+        self.keys.contains(key)
+    }
+
+    pub(crate) fn swallows_mouse(&self, button: V39Mouse) -> bool
+    {
+        self.mouse.contains(button)
+    }
+
+    pub(crate) fn swallows_pad(&self, button: V39Pad) -> bool
+    {
+        self.pad.contains(button)
+    }
+
+    pub(crate) fn begin_frame(&mut self)
+    {
+        self.keys.begin_frame();
+        self.mouse.begin_frame();
+        self.pad.begin_frame();
+    }
+
+    /// Everything got released without release events, e.g. on focus loss.
+    pub(crate) fn release_all(&mut self)
+    {
+        self.keys = Swallowed::default();
+        self.mouse = Swallowed::default();
+        self.pad = Swallowed::default();
+        self.modifier = None;
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn captures_key_with_modifiers()
+    {
+        let mut tracker = CaptureTracker::default();
+        let capture = tracker.start();
+
+        tracker.key_down(V39Key::Ctrl, Modifiers::NONE);
+        assert!(capture.is_pending());
+
+        tracker.key_down(V39Key::S, Modifiers::CTRL);
+        assert_eq!(capture.captured(), Some(CapturedInput::Key(Shortcut::new(Modifiers::CTRL, V39Key::S))));
+
+        assert!(tracker.swallows_key(V39Key::Ctrl));
+        assert!(tracker.swallows_key(V39Key::S));
+    }
+
+    #[test]
+    fn lone_modifier_is_captured_on_release()
+    {
+        let mut tracker = CaptureTracker::default();
+        let capture = tracker.start();
+
+        tracker.key_down(V39Key::Shift, Modifiers::SHIFT);
+        tracker.key_up(V39Key::Shift);
+
+        assert_eq!(capture.captured(), Some(CapturedInput::Key(Shortcut::new(Modifiers::NONE, V39Key::Shift))));
+    }
+
+    #[test]
+    fn esc_cancels()
+    {
+        let mut tracker = CaptureTracker::default();
+        let capture = tracker.start();
+
+        tracker.key_down(V39Key::Esc, Modifiers::NONE);
+
+        assert_eq!(capture.state(), CaptureState::Cancelled);
+        assert!(tracker.swallows_key(V39Key::Esc));
+    }
+
+    #[test]
+    fn swallowed_until_the_frame_after_release()
+    {
+        let mut tracker = CaptureTracker::default();
+        tracker.start();
+
+        tracker.mouse_down(V39Mouse::Left);
+        tracker.begin_frame();
+        assert!(tracker.swallows_mouse(V39Mouse::Left));
+
+        tracker.mouse_up(V39Mouse::Left);
+        assert!(tracker.swallows_mouse(V39Mouse::Left));
+
+        tracker.begin_frame();
+        assert!(!tracker.swallows_mouse(V39Mouse::Left));
+    }
+
+    #[test]
+    fn nothing_is_swallowed_without_capture()
+    {
+        let mut tracker = CaptureTracker::default();
+        let capture = tracker.start();
+        capture.cancel();
+
+        tracker.pad_down(V39Pad::A);
+
+        assert_eq!(capture.state(), CaptureState::Cancelled);
+        assert!(!tracker.swallows_pad(V39Pad::A));
+    }
+
+    #[test]
+    fn new_capture_cancels_the_previous_one()
+    {
+        let mut tracker = CaptureTracker::default();
+        let first = tracker.start();
+        let second = tracker.start();
+
+        tracker.key_down(V39Key::F, Modifiers::NONE);
+
+        assert_eq!(first.state(), CaptureState::Cancelled);
+        assert_eq!(second.captured(), Some(CapturedInput::Key(Shortcut::new(Modifiers::NONE, V39Key::F))));
+    }
+}
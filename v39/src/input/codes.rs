@@ -1,11 +1,11 @@
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 #[allow(non_camel_case_types)]
 pub enum V39Key
 {
     A, B, C, D, E, F, G, H, I, J, K, L, M,
     N, O, P, Q, R, S, T, U, V, W, X, Y, Z,              // Latin Alphabet
     
-    D1, D2, D3, D5, D6, D7, D8, D9, D0,                 // Digits
+    D1, D2, D3, D4, D5, D6, D7, D8, D9, D0,             // Digits
                                             
     ExclaimationMark, Tilde, DoubleQuote, 
     Comma, Dot, DoubleDot, Semicolon, Hashtag,
@@ -14,7 +14,7 @@ pub enum V39Key
 
     Tab, Caps, Shift, Ctrl, Super, Alt, Esc,
     Backspace, Enter, RightAlt, RightCtrl, 
-    RightShift, F1, F2, F3, F5, F6, F7, F8, F9, F10,
+    RightShift, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
     F11, F12, PrtScn, Pause, ScrLck, Insert, Delete,
     Home, End, Pageup, Pagedown, Left, Right, Up, Down, // Special Keys
 
@@ -22,13 +22,107 @@ pub enum V39Key
 }
 
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+impl V39Key
+{
+    pub const ALL: &'static [V39Key] = &[
+        V39Key::A, V39Key::B, V39Key::C, V39Key::D, V39Key::E, V39Key::F, V39Key::G, V39Key::H, V39Key::I,
+        V39Key::J, V39Key::K, V39Key::L, V39Key::M, V39Key::N, V39Key::O, V39Key::P, V39Key::Q, V39Key::R,
+        V39Key::S, V39Key::T, V39Key::U, V39Key::V, V39Key::W, V39Key::X, V39Key::Y, V39Key::Z,
+        V39Key::D1, V39Key::D2, V39Key::D3, V39Key::D4, V39Key::D5, V39Key::D6, V39Key::D7, V39Key::D8, V39Key::D9, V39Key::D0,
+        V39Key::ExclaimationMark, V39Key::Tilde, V39Key::DoubleQuote, V39Key::Comma, V39Key::Dot,
+        V39Key::DoubleDot, V39Key::Semicolon, V39Key::Hashtag, V39Key::Underscore, V39Key::Space,
+        V39Key::Dash, V39Key::Plus, V39Key::Star, V39Key::SingleQuote, V39Key::Greater, V39Key::Less,
+        V39Key::Equal, V39Key::Pipe, V39Key::Percent, V39Key::AndSign,
+        V39Key::Tab, V39Key::Caps, V39Key::Shift, V39Key::Ctrl, V39Key::Super, V39Key::Alt, V39Key::Esc,
+        V39Key::Backspace, V39Key::Enter, V39Key::RightAlt, V39Key::RightCtrl, V39Key::RightShift,
+        V39Key::F1, V39Key::F2, V39Key::F3, V39Key::F4, V39Key::F5, V39Key::F6, V39Key::F7, V39Key::F8, V39Key::F9,
+        V39Key::F10, V39Key::F11, V39Key::F12, V39Key::PrtScn, V39Key::Pause, V39Key::ScrLck,
+        V39Key::Insert, V39Key::Delete, V39Key::Home, V39Key::End, V39Key::Pageup, V39Key::Pagedown,
+        V39Key::Left, V39Key::Right, V39Key::Up, V39Key::Down,
+        V39Key::Ä, V39Key::Ü, V39Key::Ö, V39Key::ß,
+    ];
+}
+
+
+impl std::str::FromStr for V39Key
+{
+    type Err = ();
+
+    /// Accepts the variant names case insensitively as well as the usual spellings of special keys.
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let alias = match s.to_lowercase().as_str()
+        {
+            "0" => Some(V39Key::D0), "1" => Some(V39Key::D1), "2" => Some(V39Key::D2),
+            "3" => Some(V39Key::D3), "4" => Some(V39Key::D4), "5" => Some(V39Key::D5),
+            "6" => Some(V39Key::D6), "7" => Some(V39Key::D7), "8" => Some(V39Key::D8),
+            "9" => Some(V39Key::D9),
+            "escape" => Some(V39Key::Esc),
+            "return" => Some(V39Key::Enter),
+            "del" => Some(V39Key::Delete),
+            "control" => Some(V39Key::Ctrl),
+            "pageup" | "pgup" => Some(V39Key::Pageup),
+            "pagedown" | "pgdn" => Some(V39Key::Pagedown),
+            "+" => Some(V39Key::Plus),
+            "-" => Some(V39Key::Dash),
+            "," => Some(V39Key::Comma),
+            "." => Some(V39Key::Dot),
+            "=" => Some(V39Key::Equal),
+            _ => None,
+        };
+
+        if let Some(key) = alias
+        {
+            return Ok(key);
+        }
+
+        V39Key::ALL.iter()
+            .find(|k| format!("{k:?}").to_lowercase() == s.to_lowercase())
+            .copied()
+            .ok_or(())
+    }
+}
+
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum V39Pad
 {
     A, B, X, Y,
     Left, Right, Up, Down,
-    LStick(f32, f32), RStick(f32, f32),
+    LStick, RStick,                                     // Stick presses, the position is a V39Axis
     Select, Start, Home,
     TriggerLeft, BumperLeft, TriggerRight, BumberRight,
 }
 
+
+impl V39Pad
+{
+    pub const ALL: &'static [V39Pad] = &[
+        V39Pad::A, V39Pad::B, V39Pad::X, V39Pad::Y,
+        V39Pad::Left, V39Pad::Right, V39Pad::Up, V39Pad::Down,
+        V39Pad::LStick, V39Pad::RStick,
+        V39Pad::Select, V39Pad::Start, V39Pad::Home,
+        V39Pad::TriggerLeft, V39Pad::BumperLeft, V39Pad::TriggerRight, V39Pad::BumberRight,
+    ];
+}
+
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum V39Mouse
+{
+    Left, Right, Middle, Back, Forward,
+}
+
+
+impl V39Mouse
+{
+    pub const ALL: &'static [V39Mouse] = &[V39Mouse::Left, V39Mouse::Right, V39Mouse::Middle, V39Mouse::Back, V39Mouse::Forward];
+}
+
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum V39Axis
+{
+    LStick, RStick,                                     // Two dimensional, x and y in -1..=1
+    TriggerLeft, TriggerRight,                          // One dimensional, only x is used in 0..=1
+}
@@ -0,0 +1,327 @@
+use crate::input::V39Key;
+use crate::input::repeat::KeyRepeat;
+
+
+/// Where an input is looked at from when resolving what the context stack hides.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub(crate) enum InputLayer<'a>
+{
+    /// Engine receivers which have to track the full input state, they see everything.
+    Raw,
+
+    /// Below every pushed context.
+    Base,
+    Context(&'a str),
+}
+
+
+impl<'a> From<Option<&'a str>> for InputLayer<'a>
+{
+    fn from(context: Option<&'a str>) -> Self
+    {
+        match context
+        {
+            Some(name) => InputLayer::Context(name),
+            None => InputLayer::Base,
+        }
+    }
+}
+
+
+/// A named input layer, e.g. "gameplay", "pause_menu" or "text_field".
+/// Contexts are stacked, a context hides the keys it consumes from every context below it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputContext
+{
+    pub name: String,
+    actions: Vec<(String, V39Key)>,
+    consumed_keys: Vec<V39Key>,
+    consumed_actions: Vec<String>,
+    consumes_all: bool,
+    repeat: Option<KeyRepeat>,
+    action_repeat: Vec<(String, KeyRepeat)>,
+}
+
+
+impl InputContext
+{
+    pub fn new(name: impl Into<String>) -> Self
+    {
+        Self {
+            name: name.into(),
+            actions: vec![],
+            consumed_keys: vec![],
+            consumed_actions: vec![],
+            consumes_all: false,
+            repeat: None,
+            action_repeat: vec![],
+        }
+    }
+
+    /// Binds a key to a named action within this context.
+    pub fn bind(mut self, action: impl Into<String>, key: V39Key) -> Self
+    {
+        self.actions.push((action.into(), key));
+        self
+    }
+
+    /// Hides the key from all contexts below this one.
+    pub fn consume(mut self, key: V39Key) -> Self
+    {
+        self.consumed_keys.push(key);
+        self
+    }
+
+    /// Hides every key bound to the action from all contexts below this one,
+    /// including keys bound after this call.
+    pub fn consume_action(mut self, action: impl Into<String>) -> Self
+    {
+        self.consumed_actions.push(action.into());
+        self
+    }
+
+    /// Hides all input from the contexts below this one, e.g. for a text field.
+    /// Besides keys this covers mouse, pad and touch input, releases are still delivered.
+    pub fn consume_all(mut self) -> Self
+    {
+        self.consumes_all = true;
+        self
+    }
+
+    /// Key repeat for every key this context sees, overrides the global setting.
+    pub fn repeat(mut self, repeat: KeyRepeat) -> Self
+    {
+        self.repeat = Some(repeat);
+        self
+    }
+
+    /// Key repeat for the keys bound to the action, overrides the repeat of the context.
+    pub fn repeat_action(mut self, action: impl Into<String>, repeat: KeyRepeat) -> Self
+    {
+        self.action_repeat.push((action.into(), repeat));
+        self
+    }
+
+    pub fn repeat_for(&self, key: V39Key) -> Option<KeyRepeat>
+    {
+        let action = self.action_repeat.iter()
+            .find(|(action, _)| self.action_keys(action).contains(&key))
+            .map(|(_, repeat)| *repeat);
+
+        action.or(self.repeat)
+    }
+
+    pub fn consumes(&self, key: V39Key) -> bool
+    {
+        self.consumes_all
+            || self.consumed_keys.contains(&key)
+            || self.consumed_actions.iter().any(|action| self.action_keys(action).contains(&key))
+    }
+
+    /// Whether input other than keys is hidden from the contexts below.
+    pub fn consumes_all(&self) -> bool
+    {
+        self.consumes_all
+    }
+
+    pub fn action_keys(&self, action: &str) -> Vec<V39Key>
+    {
+        self.actions.iter()
+            .filter(|(name, _)| name == action)
+            .map(|(_, key)| *key)
+            .collect()
+    }
+}
+
+
+#[derive(Default)]
+pub(crate) struct ContextStack
+{
+    contexts: Vec<InputContext>,
+}
+
+
+impl ContextStack
+{
+    pub(crate) fn push(&mut self, context: InputContext)
+    {
+        self.remove(&context.name);
+        self.contexts.push(context);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<InputContext>
+    {
+        self.contexts.pop()
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> Option<InputContext>
+    {
+        let index = self.contexts.iter().position(|c| c.name == name)?;
+        Some(self.contexts.remove(index))
+    }
+
+    pub(crate) fn top(&self) -> Option<&InputContext>
+    {
+        self.contexts.last()
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool
+    {
+        self.contexts.iter().any(|c| c.name == name)
+    }
+
+    /// The contexts above the layer, `None` for contexts which are not on the stack.
+    fn above(&self, layer: InputLayer) -> Option<&[InputContext]>
+    {
+        match layer
+        {
+            InputLayer::Raw => Some(&[]),
+            InputLayer::Base => Some(&self.contexts[..]),
+
+            InputLayer::Context(name) => self.contexts.iter()
+                .position(|c| c.name == name)
+                .map(|index| &self.contexts[index+1..]),
+        }
+    }
+
+    /// Whether a key reaches the given layer.
+    /// Contexts which are not on the stack receive nothing.
+    pub(crate) fn visible(&self, key: V39Key, layer: InputLayer) -> bool
+    {
+        self.above(layer).is_some_and(|above| !above.iter().any(|c| c.consumes(key)))
+    }
+
+    /// Whether mouse, pad and touch input reaches the given layer.
+    pub(crate) fn input_visible(&self, layer: InputLayer) -> bool
+    {
+        self.above(layer).is_some_and(|above| !above.iter().any(|c| c.consumes_all))
+    }
+
+    /// Resolves the key repeat from the top of the stack downwards.
+    /// `None` if no context which sees the key configures a repeat.
+    pub(crate) fn repeat_for(&self, key: V39Key) -> Option<KeyRepeat>
+    {
+        for context in self.contexts.iter().rev()
+        {
+            if let Some(repeat) = context.repeat_for(key)
+            {
+                return Some(repeat);
+            }
+
+            if context.consumes(key) {break}
+        }
+
+        None
+    }
+
+    /// Resolves an action from the top of the stack downwards.
+    /// The first context binding the action decides which keys it maps to.
+    pub(crate) fn resolve_action(&self, action: &str) -> Option<(String, Vec<V39Key>)>
+    {
+        self.contexts.iter()
+            .rev()
+            .map(|c| (c, c.action_keys(action)))
+            .find(|(_, keys)| !keys.is_empty())
+            .map(|(c, keys)| (c.name.clone(), keys))
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn stack() -> ContextStack
+    {
+        let mut stack = ContextStack::default();
+        stack.push(InputContext::new("gameplay").bind("jump", V39Key::Space).bind("menu", V39Key::Esc));
+        stack.push(InputContext::new("pause_menu").bind("back", V39Key::Esc).consume_action("back"));
+        stack
+    }
+
+    #[test]
+    fn consumed_keys_are_hidden_below()
+    {
+        let stack = stack();
+
+        assert!(stack.visible(V39Key::Esc, InputLayer::Context("pause_menu")));
+        assert!(!stack.visible(V39Key::Esc, InputLayer::Context("gameplay")));
+        assert!(!stack.visible(V39Key::Esc, InputLayer::Base));
+
+        assert!(stack.visible(V39Key::Space, InputLayer::Context("gameplay")));
+        assert!(stack.visible(V39Key::Space, InputLayer::Base));
+    }
+
+    #[test]
+    fn consume_all_hides_everything_below()
+    {
+        let mut stack = stack();
+        assert!(stack.input_visible(InputLayer::Base));
+
+        stack.push(InputContext::new("text_field").consume_all());
+
+        assert!(stack.visible(V39Key::A, InputLayer::Context("text_field")));
+        assert!(!stack.visible(V39Key::A, InputLayer::Context("pause_menu")));
+        assert!(!stack.visible(V39Key::Space, InputLayer::Base));
+
+        assert!(stack.input_visible(InputLayer::Context("text_field")));
+        assert!(!stack.input_visible(InputLayer::Context("pause_menu")));
+        assert!(!stack.input_visible(InputLayer::Base));
+        assert!(stack.input_visible(InputLayer::Raw));
+        assert!(!stack.input_visible(InputLayer::Context("unknown")));
+
+        stack.pop();
+        assert!(stack.visible(V39Key::Space, InputLayer::Base));
+        assert!(stack.input_visible(InputLayer::Base));
+    }
+
+    #[test]
+    fn consumed_actions_resolve_their_keys_late()
+    {
+        let mut stack = ContextStack::default();
+        stack.push(InputContext::new("gameplay"));
+        stack.push(InputContext::new("dialog").consume_action("confirm").bind("confirm", V39Key::Enter));
+
+        assert!(!stack.visible(V39Key::Enter, InputLayer::Context("gameplay")));
+        assert!(stack.visible(V39Key::Space, InputLayer::Context("gameplay")));
+    }
+
+    #[test]
+    fn only_the_engine_sees_through_contexts()
+    {
+        let mut stack = stack();
+        stack.push(InputContext::new("text_field").consume_all());
+
+        assert!(stack.visible(V39Key::Esc, InputLayer::Raw));
+        assert!(!stack.visible(V39Key::Esc, InputLayer::Context("v39::raw")));
+        assert!(!stack.visible(V39Key::Esc, InputLayer::Context("unknown")));
+    }
+
+    #[test]
+    fn actions_resolve_from_the_top()
+    {
+        let mut stack = stack();
+
+        assert_eq!(stack.resolve_action("menu"), Some(("gameplay".into(), vec![V39Key::Esc])));
+        assert_eq!(stack.resolve_action("back"), Some(("pause_menu".into(), vec![V39Key::Esc])));
+        assert_eq!(stack.resolve_action("crouch"), None);
+
+        stack.push(InputContext::new("vehicle").bind("jump", V39Key::J).bind("jump", V39Key::Enter));
+        assert_eq!(stack.resolve_action("jump"), Some(("vehicle".into(), vec![V39Key::J, V39Key::Enter])));
+
+        stack.remove("vehicle");
+        assert_eq!(stack.resolve_action("jump"), Some(("gameplay".into(), vec![V39Key::Space])));
+    }
+
+    #[test]
+    fn pushing_again_moves_to_the_top()
+    {
+        let mut stack = stack();
+        stack.push(InputContext::new("gameplay"));
+
+        assert_eq!(stack.top().unwrap().name, "gameplay");
+        assert!(stack.visible(V39Key::Esc, InputLayer::Context("gameplay")));
+        assert_eq!(stack.resolve_action("jump"), None);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::input::V39Key;
+use std::time::Duration;
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gesture
+{
+    /// All keys held at the same time, e.g. Ctrl+Shift+S. Fires once when the chord completes.
+    Chord(Vec<V39Key>),
+
+    /// Keys pressed in order, each one at most `timeout` after the previous one.
+    Sequence {keys: Vec<V39Key>, timeout: Duration},
+
+    /// The same key pressed twice within `window`.
+    DoubleTap {key: V39Key, window: Duration},
+
+    /// A key held for at least `duration`. Fires once per press.
+    LongPress {key: V39Key, duration: Duration},
+}
+
+
+#[derive(Clone, Debug, Default)]
+struct GestureState
+{
+    progress: usize,
+    last_press: Option<Duration>,
+    fired: bool,
+}
+
+
+#[derive(Default)]
+pub(crate) struct GestureRecognizer
+{
+    gestures: Vec<(u32, Gesture, GestureState)>,
+    pressed: Vec<V39Key>,
+}
+
+
+impl GestureRecognizer
+{
+    pub(crate) fn add(&mut self, id: u32, gesture: Gesture)
+    {
+        self.gestures.push((id, gesture, GestureState::default()));
+    }
+
+    pub(crate) fn remove(&mut self, id: u32)
+    {
+        self.gestures.retain(|(gid, _, _)| *gid != id);
+    }
+
+    /// Feeds a key press into the recognizer and returns the ids of every gesture it completed.
+    pub(crate) fn key_down(&mut self, key: V39Key, now: Duration) -> Vec<u32>
+    {
+        if !self.pressed.contains(&key)
+        {
+            self.pressed.push(key);
+        }
+
+        let mut recognized = vec![];
+
+        for (id, gesture, state) in self.gestures.iter_mut()
+        {
+            match gesture
+            {
+                Gesture::Chord(keys) => {
+                    let complete = keys.contains(&key) && keys.iter().all(|k| self.pressed.contains(k));
+
+                    if complete && !state.fired
+                    {
+                        state.fired = true;
+                        recognized.push(*id);
+                    }
+                },
+
+                Gesture::Sequence {keys, timeout} => {
+                    let in_time = state.last_press.is_none_or(|last| now.saturating_sub(last) <= *timeout);
+                    let matched = if in_time {state.progress} else {0};
+
+                    state.progress = sequence_progress(keys, matched, key);
+                    state.last_press = Some(now);
+
+                    if state.progress == keys.len()
+                    {
+                        state.progress = 0;
+                        state.last_press = None;
+                        recognized.push(*id);
+                    }
+                },
+
+                Gesture::DoubleTap {key: target, window} => {
+                    if key != *target
+                    {
+                        state.last_press = None;
+                    }
+
+                    else if state.last_press.is_some_and(|last| now.saturating_sub(last) <= *window)
+                    {
+                        state.last_press = None;
+                        recognized.push(*id);
+                    }
+
+                    else
+                    {
+                        state.last_press = Some(now);
+                    }
+                },
+
+                Gesture::LongPress {key: target, ..} => {
+                    if key == *target
+                    {
+                        state.last_press = Some(now);
+                        state.fired = false;
+                    }
+                },
+            }
+        }
+
+        recognized
+    }
+
+    pub(crate) fn key_up(&mut self, key: V39Key)
+    {
+        self.pressed.retain(|k| *k != key);
+
+        for (_, gesture, state) in self.gestures.iter_mut()
+        {
+            match gesture
+            {
+                Gesture::Chord(keys) if keys.contains(&key) => state.fired = false,
+                Gesture::LongPress {key: target, ..} if *target == key => state.last_press = None,
+                _ => {},
+            }
+        }
+    }
+
+    /// Forgets held keys and partial gestures, e.g. when the window loses focus.
+    pub(crate) fn reset(&mut self)
+    {
+        self.pressed.clear();
+
+        for (_, _, state) in self.gestures.iter_mut()
+        {
+            *state = GestureState::default();
+        }
+    }
+
+    /// Checks the time based gestures, should be called once per frame.
+    pub(crate) fn update(&mut self, now: Duration) -> Vec<u32>
+    {
+        let mut recognized = vec![];
+
+        for (id, gesture, state) in self.gestures.iter_mut()
+        {
+            if let (Gesture::LongPress {duration, ..}, Some(pressed)) = (gesture, state.last_press)
+            {
+                if !state.fired && now.saturating_sub(pressed) >= *duration
+                {
+                    state.fired = true;
+                    recognized.push(*id);
+                }
+            }
+        }
+
+        recognized
+    }
+}
+
+
+/// How much of `keys` is matched once `key` follows the first `matched` keys.
+/// On a wrong key the longest end of the input which starts `keys` is kept,
+/// so Up, Up, Up, Down still completes Up, Up, Down.
+fn sequence_progress(keys: &[V39Key], matched: usize, key: V39Key) -> usize
+{
+    for len in (1..=(matched + 1).min(keys.len())).rev()
+    {
+        let prefix = &keys[..len - 1];
+
+        if keys[len - 1] == key && keys[matched + 1 - len..matched] == *prefix
+        {
+            return len;
+        }
+    }
+
+    0
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ms(ms: u64) -> Duration
+    {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn chord_fires_once_per_completion()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(1, Gesture::Chord(vec![V39Key::Ctrl, V39Key::Shift, V39Key::S]));
+
+        assert!(recognizer.key_down(V39Key::Ctrl, ms(0)).is_empty());
+        assert!(recognizer.key_down(V39Key::Shift, ms(10)).is_empty());
+        assert_eq!(recognizer.key_down(V39Key::S, ms(20)), [1]);
+
+        // Holding the chord does not fire again, releasing one key rearms it
+        assert!(recognizer.key_down(V39Key::S, ms(30)).is_empty());
+        recognizer.key_up(V39Key::S);
+        assert_eq!(recognizer.key_down(V39Key::S, ms(40)), [1]);
+
+        recognizer.key_up(V39Key::Ctrl);
+        recognizer.key_up(V39Key::S);
+        assert!(recognizer.key_down(V39Key::S, ms(50)).is_empty());
+    }
+
+    #[test]
+    fn sequence_times_out_between_keys()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(2, Gesture::Sequence {keys: vec![V39Key::Up, V39Key::Up, V39Key::Down], timeout: ms(300)});
+
+        assert!(recognizer.key_down(V39Key::Up, ms(0)).is_empty());
+        assert!(recognizer.key_down(V39Key::Up, ms(100)).is_empty());
+        assert_eq!(recognizer.key_down(V39Key::Down, ms(200)), [2]);
+
+        // Too slow, the late Up starts a new attempt which the Down then breaks
+        recognizer.key_down(V39Key::Up, ms(1000));
+        recognizer.key_down(V39Key::Up, ms(1500));
+        assert!(recognizer.key_down(V39Key::Down, ms(1600)).is_empty());
+
+        recognizer.key_down(V39Key::Up, ms(2000));
+        recognizer.key_down(V39Key::Up, ms(2200));
+        assert!(recognizer.key_down(V39Key::Down, ms(2600)).is_empty());
+    }
+
+    #[test]
+    fn sequence_restarts_on_the_first_key()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(3, Gesture::Sequence {keys: vec![V39Key::A, V39Key::B], timeout: ms(300)});
+
+        recognizer.key_down(V39Key::A, ms(0));
+        recognizer.key_down(V39Key::A, ms(100));
+        assert_eq!(recognizer.key_down(V39Key::B, ms(200)), [3]);
+    }
+
+    #[test]
+    fn sequence_keeps_overlapping_prefixes()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(5, Gesture::Sequence {keys: vec![V39Key::Up, V39Key::Up, V39Key::Down], timeout: ms(300)});
+
+        recognizer.key_down(V39Key::Up, ms(0));
+        recognizer.key_down(V39Key::Up, ms(100));
+        recognizer.key_down(V39Key::Up, ms(200));
+        assert_eq!(recognizer.key_down(V39Key::Down, ms(300)), [5]);
+
+        assert_eq!(sequence_progress(&[V39Key::A, V39Key::B, V39Key::A, V39Key::C], 3, V39Key::B), 2);
+        assert_eq!(sequence_progress(&[V39Key::A, V39Key::B], 1, V39Key::C), 0);
+    }
+
+    #[test]
+    fn reset_forgets_held_keys_and_progress()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(6, Gesture::Chord(vec![V39Key::Ctrl, V39Key::S]));
+        recognizer.add(7, Gesture::Sequence {keys: vec![V39Key::A, V39Key::B], timeout: ms(300)});
+
+        recognizer.key_down(V39Key::Ctrl, ms(0));
+        recognizer.key_down(V39Key::A, ms(10));
+        recognizer.reset();
+
+        assert!(recognizer.key_down(V39Key::S, ms(20)).is_empty());
+        assert!(recognizer.key_down(V39Key::B, ms(30)).is_empty());
+    }
+
+    #[test]
+    fn double_tap_within_the_window()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(4, Gesture::DoubleTap {key: V39Key::W, window: ms(250)});
+
+        recognizer.key_down(V39Key::W, ms(0));
+        assert_eq!(recognizer.key_down(V39Key::W, ms(200)), [4]);
+
+        // A third tap starts over instead of firing again
+        assert!(recognizer.key_down(V39Key::W, ms(300)).is_empty());
+
+        // Too slow
+        recognizer.key_down(V39Key::W, ms(1000));
+        assert!(recognizer.key_down(V39Key::W, ms(1400)).is_empty());
+        assert_eq!(recognizer.key_down(V39Key::W, ms(1500)), [4]);
+
+        // Another key in between breaks the taps
+        recognizer.key_down(V39Key::W, ms(2000));
+        recognizer.key_down(V39Key::A, ms(2050));
+        assert!(recognizer.key_down(V39Key::W, ms(2100)).is_empty());
+    }
+
+    #[test]
+    fn long_press_fires_once_while_held()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(5, Gesture::LongPress {key: V39Key::E, duration: ms(500)});
+
+        recognizer.key_down(V39Key::E, ms(0));
+        assert!(recognizer.update(ms(400)).is_empty());
+        assert_eq!(recognizer.update(ms(500)), [5]);
+        assert!(recognizer.update(ms(900)).is_empty());
+
+        recognizer.key_up(V39Key::E);
+        assert!(recognizer.update(ms(1000)).is_empty());
+
+        // Released early
+        recognizer.key_down(V39Key::E, ms(2000));
+        recognizer.key_up(V39Key::E);
+        assert!(recognizer.update(ms(3000)).is_empty());
+    }
+
+    #[test]
+    fn removed_gestures_do_not_fire()
+    {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.add(6, Gesture::DoubleTap {key: V39Key::Space, window: ms(250)});
+        recognizer.remove(6);
+
+        recognizer.key_down(V39Key::Space, ms(0));
+        assert!(recognizer.key_down(V39Key::Space, ms(100)).is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::input::{V39Key, V39Mouse, V39Pad, V39Axis, Modifiers, Touch};
+use crate::event::EngineEvent;
+
+
+/// A single input as a player would produce it.
+/// Injected inputs take the same path through the engine as inputs coming from the window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent
+{
+    KeyDown(V39Key),
+    KeyUp(V39Key),
+    MouseDown(V39Mouse),
+    MouseUp(V39Mouse),
+    MouseMove(f64, f64),
+    MouseMotion(f64, f64),
+    PadDown(V39Pad),
+    PadUp(V39Pad),
+    PadAxis(V39Axis, (f32, f32)),
+    Touch(Touch),
+}
+
+
+impl InputEvent
+{
+    /// Converts the input into its engine event, key events carry the given modifiers.
+    pub(crate) fn into_engine_event(self, modifiers: Modifiers) -> EngineEvent
+    {
+        match self
+        {
+            InputEvent::KeyDown(key) => EngineEvent::KeyDown(Some((key, modifiers))),
+            InputEvent::KeyUp(key) => EngineEvent::KeyUp(Some((key, modifiers))),
+            InputEvent::MouseDown(button) => EngineEvent::MouseDown(Some(button)),
+            InputEvent::MouseUp(button) => EngineEvent::MouseUp(Some(button)),
+            InputEvent::MouseMove(x, y) => EngineEvent::MouseMove(Some((x, y))),
+            InputEvent::MouseMotion(dx, dy) => EngineEvent::MouseMotion(Some((dx, dy))),
+            InputEvent::PadDown(button) => EngineEvent::PadDown(Some(button)),
+            InputEvent::PadUp(button) => EngineEvent::PadUp(Some(button)),
+            InputEvent::PadAxis(axis, value) => EngineEvent::PadAxis(Some((axis, value))),
+            InputEvent::Touch(touch) => EngineEvent::Touch(Some(touch)),
+        }
+    }
+}
+
+
+/// Injected inputs waiting for their frame, kept in the order they were injected.
+#[derive(Default)]
+pub(crate) struct InjectionQueue
+{
+    pending: Vec<(u64, InputEvent)>,
+}
+
+
+impl InjectionQueue
+{
+    pub(crate) fn push(&mut self, frame: u64, event: InputEvent)
+    {
+        self.pending.push((frame, event));
+    }
+
+    /// Removes and returns every input due at or before the given frame.
+    pub(crate) fn take_due(&mut self, frame: u64) -> Vec<InputEvent>
+    {
+        let mut due = vec![];
+        let mut waiting = vec![];
+
+        for (at, event) in self.pending.drain(..)
+        {
+            if at <= frame {due.push(event)}
+            else {waiting.push((at, event))}
+        }
+
+        self.pending = waiting;
+        due
+    }
+
+    pub(crate) fn len(&self) -> usize
+    {
+        self.pending.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn same_frame_keeps_the_injection_order()
+    {
+        let mut queue = InjectionQueue::default();
+        queue.push(3, InputEvent::KeyDown(V39Key::D));
+        queue.push(3, InputEvent::KeyUp(V39Key::D));
+        queue.push(3, InputEvent::KeyDown(V39Key::D));
+
+        assert_eq!(queue.take_due(3), [InputEvent::KeyDown(V39Key::D), InputEvent::KeyUp(V39Key::D), InputEvent::KeyDown(V39Key::D)]);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn past_frames_fire_on_the_next_flush()
+    {
+        let mut queue = InjectionQueue::default();
+        queue.push(10, InputEvent::MouseDown(V39Mouse::Left));
+        queue.push(2, InputEvent::PadDown(V39Pad::A));
+
+        assert_eq!(queue.take_due(11), [InputEvent::MouseDown(V39Mouse::Left), InputEvent::PadDown(V39Pad::A)]);
+        assert!(queue.take_due(12).is_empty());
+    }
+
+    #[test]
+    fn future_frames_do_not_fire_early()
+    {
+        let mut queue = InjectionQueue::default();
+        queue.push(5, InputEvent::KeyDown(V39Key::W));
+        queue.push(6, InputEvent::KeyUp(V39Key::W));
+
+        assert!(queue.take_due(4).is_empty());
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.take_due(5), [InputEvent::KeyDown(V39Key::W)]);
+        assert_eq!(queue.len(), 1);
+
+        assert_eq!(queue.take_due(6), [InputEvent::KeyUp(V39Key::W)]);
+        assert_eq!(queue.len(), 0);
+    }
+}
@@ -1,11 +1,34 @@
 pub(crate) mod translate;
 
 pub mod codes;
-pub use codes::{V39Pad, V39Key};
+pub mod axis;
+pub mod gesture;
+pub mod context;
+pub mod inject;
+pub mod repeat;
+pub mod modifiers;
+pub mod touch;
+pub mod capture;
+pub(crate) mod state;
+pub use codes::{V39Pad, V39Key, V39Mouse, V39Axis};
+pub use inject::InputEvent;
+pub use modifiers::{Modifiers, Shortcut};
+pub use touch::{Touch, TouchPhase, TouchGesture};
 
 use once_cell::sync::OnceCell;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 use crate::prelude::*;
+use crate::window::WindowId;
+use gesture::{Gesture, GestureRecognizer};
+use context::{InputContext, ContextStack};
+use inject::InjectionQueue;
+use state::ButtonState;
+use repeat::{KeyRepeat, RepeatTracker};
+use touch::TouchTracker;
+use capture::{CaptureTracker, InputCapture};
+use context::InputLayer;
 
 
 static INSTANCE: OnceCell<InputManager> = OnceCell::new();
@@ -13,12 +36,20 @@ static INSTANCE: OnceCell<InputManager> = OnceCell::new();
 
 pub(crate) struct InputManager
 {
-    keys_down: Mutex<Vec<V39Key>>,
-    keys_up: Mutex<Vec<V39Key>>,
-    keys_held: Mutex<Vec<V39Key>>,
-    snapshot_keys_down: Mutex<Vec<V39Key>>,
-    snapshot_keys_up: Mutex<Vec<V39Key>>,
-    snapshot_keys_held: Mutex<Vec<V39Key>>,
+    keys: Mutex<ButtonState<V39Key>>,
+    mouse: Mutex<ButtonState<V39Mouse>>,
+    pad: Mutex<ButtonState<V39Pad>>,
+    axes: Mutex<HashMap<V39Axis, (f32, f32)>>,
+    gestures: Mutex<GestureRecognizer>,
+    contexts: Mutex<ContextStack>,
+    mouse_position: Mutex<(f64, f64, WindowId)>,
+    mouse_delta: Mutex<(f64, f64)>,
+    injected: Mutex<InjectionQueue>,
+    repeat: Mutex<KeyRepeat>,
+    repeats: Mutex<RepeatTracker>,
+    modifiers: Mutex<Modifiers>,
+    touches: Mutex<TouchTracker>,
+    capture: Mutex<CaptureTracker>,
 }
 
 
@@ -27,12 +58,20 @@ impl InputManager
     pub(crate) fn init() -> V39Result<&'static InputManager>
     {
         let input_manager = InputManager {
-            keys_down: Mutex::new(vec![]),
-            keys_up: Mutex::new(vec![]),
-            keys_held: Mutex::new(vec![]),
-            snapshot_keys_down: Mutex::new(vec![]),
-            snapshot_keys_up: Mutex::new(vec![]),
-            snapshot_keys_held: Mutex::new(vec![]),
+            keys: Mutex::new(ButtonState::default()),
+            mouse: Mutex::new(ButtonState::default()),
+            pad: Mutex::new(ButtonState::default()),
+            axes: Mutex::new(HashMap::new()),
+            gestures: Mutex::new(GestureRecognizer::default()),
+            contexts: Mutex::new(ContextStack::default()),
+            mouse_position: Mutex::new((0.0, 0.0, WindowId::MAIN)),
+            mouse_delta: Mutex::new((0.0, 0.0)),
+            injected: Mutex::new(InjectionQueue::default()),
+            repeat: Mutex::new(KeyRepeat::Off),
+            repeats: Mutex::new(RepeatTracker::default()),
+            modifiers: Mutex::new(Modifiers::NONE),
+            touches: Mutex::new(TouchTracker::default()),
+            capture: Mutex::new(CaptureTracker::default()),
         };
         
         if INSTANCE.set(input_manager).is_err()
@@ -48,156 +87,343 @@ impl InputManager
         INSTANCE.get().expect("Input Manager was uninitialized")
     }
 
-    pub(crate) fn push_key_down(&self, key: V39Key)
+    pub(crate) fn keys(&self) -> MutexGuard<'_, ButtonState<V39Key>>
     {
-        if let Ok(mut keys_down) = self.keys_down.lock()
+        self.keys.lock().expect("Key state was poisoned")
+    }
+
+    pub(crate) fn mouse(&self) -> MutexGuard<'_, ButtonState<V39Mouse>>
+    {
+        self.mouse.lock().expect("Mouse state was poisoned")
+    }
+
+    pub(crate) fn pad(&self) -> MutexGuard<'_, ButtonState<V39Pad>>
+    {
+        self.pad.lock().expect("Pad state was poisoned")
+    }
+
+    pub(crate) fn capture(&self) -> MutexGuard<'_, CaptureTracker>
+    {
+        self.capture.lock().expect("Input capture was poisoned")
+    }
+
+    pub(crate) fn repeats(&self) -> MutexGuard<'_, RepeatTracker>
+    {
+        self.repeats.lock().expect("Key repeat state was poisoned")
+    }
+
+    pub(crate) fn set_repeat(&self, repeat: KeyRepeat)
+    {
+        if let Ok(mut global) = self.repeat.lock()
         {
-            keys_down.push(key);
+            *global = repeat;
         }
     }
 
-    pub(crate) fn push_key_up(&self, key: V39Key)
+    /// The repeat mode of the key, resolved through the context stack with the global setting as fallback.
+    pub(crate) fn repeat_mode(&self, key: V39Key) -> KeyRepeat
     {
-        if let Ok(mut keys_up) = self.keys_up.lock()
+        let context = self.contexts.lock().ok().and_then(|c| c.repeat_for(key));
+
+        match context
         {
-            keys_up.push(key);
+            Some(repeat) => repeat,
+            None => *self.repeat.lock().unwrap(),
         }
     }
 
-    pub(crate) fn snapshot_keys_down(&self)
+    /// Starts a new input frame, the pressed and released edges of the last frame are forgotten.
+    pub(crate) fn begin_frame(&self)
     {
-        if let (Ok(mut keys), Ok(mut snapshot)) 
-            = (self.keys_down.lock(), self.snapshot_keys_down.lock())
+        self.keys().begin_frame();
+        self.mouse().begin_frame();
+        self.pad().begin_frame();
+        self.capture().begin_frame();
+
+        if let Ok(mut delta) = self.mouse_delta.lock()
         {
-            *snapshot = keys.drain(..).collect::<Vec<_>>();
+            *delta = (0.0, 0.0);
         }
     }
 
-    pub(crate) fn snapshot_keys_up(&self)
+    /// Releases every button and forgets partial gestures.
+    /// Returns the releases receivers still have to be told about.
+    pub(crate) fn release_all(&self) -> Vec<InputEvent>
     {
-        if let (Ok(mut keys), Ok(mut snapshot)) 
-            = (self.keys_up.lock(), self.snapshot_keys_up.lock())
+        let mut released = vec![];
+        released.extend(self.keys().held(V39Key::ALL).into_iter().map(InputEvent::KeyUp));
+        released.extend(self.mouse().held(V39Mouse::ALL).into_iter().map(InputEvent::MouseUp));
+        released.extend(self.pad().held(V39Pad::ALL).into_iter().map(InputEvent::PadUp));
+
+        self.repeats().release_all();
+        self.keys().release_all();
+        self.mouse().release_all();
+        self.pad().release_all();
+        self.capture().release_all();
+
+        if let Ok(mut touches) = self.touches.lock()
+        {
+            touches.clear();
+        }
+
+        if let Ok(mut gestures) = self.gestures.lock()
         {
-            *snapshot = keys.drain(..).collect::<Vec<_>>();
+            gestures.reset();
         }
+
+        released
     }
 
-    pub(crate) fn snapshot_keys_held(&self)
+    /// Modifiers as reported by the window system.
+    pub(crate) fn set_modifiers(&self, modifiers: Modifiers)
     {
-        if let (Ok(mut keys), Ok(mut snapshot)) 
-            = (self.keys_held.lock(), self.snapshot_keys_held.lock())
+        if let Ok(mut current) = self.modifiers.lock()
         {
-            *snapshot = keys.drain(..).collect::<Vec<_>>();
+            *current = modifiers;
         }
     }
 
-    pub(crate) fn apply_down_snapshot(&self)
+    /// The reported modifiers combined with the held modifier keys,
+    /// the latter makes injected modifier keys count as well.
+    pub(crate) fn modifiers(&self) -> Modifiers
     {
-        if let (Ok(mut keys), Ok(mut snapshot)) 
-            = (self.keys_down.lock(), self.snapshot_keys_down.lock())
+        let mut modifiers = *self.modifiers.lock().unwrap();
+        let keys = self.keys();
+
+        for key in [V39Key::Shift, V39Key::RightShift, V39Key::Ctrl, V39Key::RightCtrl, V39Key::Alt, V39Key::RightAlt, V39Key::Super]
         {
-            keys.extend(snapshot.drain(..));
+            if keys.is_held(key)
+            {
+                modifiers |= Modifiers::from_key(key).unwrap_or_default();
+            }
         }
+
+        modifiers
     }
 
-    pub(crate) fn apply_up_snapshot(&self)
+    pub(crate) fn set_mouse_position(&self, position: (f64, f64), window: WindowId)
     {
-        if let (Ok(mut keys), Ok(mut snapshot)) 
-            = (self.keys_up.lock(), self.snapshot_keys_up.lock())
+        if let Ok(mut pos) = self.mouse_position.lock()
         {
-            keys.extend(snapshot.drain(..));
+            *pos = (position.0, position.1, window);
         }
     }
 
-    pub(crate) fn apply_held_snapshot(&self)
+    pub(crate) fn mouse_position(&self) -> (f64, f64)
+    {
+        let (x, y, _) = *self.mouse_position.lock().unwrap();
+        (x, y)
+    }
+
+    pub(crate) fn mouse_window(&self) -> WindowId
+    {
+        self.mouse_position.lock().unwrap().2
+    }
+
+    pub(crate) fn add_mouse_delta(&self, delta: (f64, f64))
     {
-        if let (Ok(mut keys), Ok(mut snapshot)) 
-            = (self.keys_held.lock(), self.snapshot_keys_held.lock())
+        if let Ok(mut total) = self.mouse_delta.lock()
         {
-            keys.extend(snapshot.drain(..));
+            total.0 += delta.0;
+            total.1 += delta.1;
         }
     }
 
-    pub(crate) fn down_snapshot_contains(&self, key: V39Key) -> bool
+    pub(crate) fn mouse_delta(&self) -> (f64, f64)
     {
-        if let Ok(keys) = self.snapshot_keys_down.lock()
+        *self.mouse_delta.lock().unwrap()
+    }
+
+    /// Tracks the touch and returns the touch gestures it produces.
+    pub(crate) fn update_touch(&self, touch: Touch, now: Duration) -> Vec<TouchGesture>
+    {
+        if let Ok(mut touches) = self.touches.lock()
         {
-            return keys.contains(&key);
+            return touches.update(touch, now);
         }
 
-        false
+        vec![]
     }
 
-    pub(crate) fn up_snapshot_contains(&self, key: V39Key) -> bool
+    pub(crate) fn touches(&self) -> Vec<Touch>
     {
-        if let Ok(keys) = self.snapshot_keys_up.lock()
+        self.touches.lock().map(|t| t.touches()).unwrap_or_default()
+    }
+
+    pub(crate) fn touch(&self, id: u64) -> Option<Touch>
+    {
+        self.touches.lock().ok()?.touch(id)
+    }
+
+    pub(crate) fn inject(&self, frame: u64, event: InputEvent)
+    {
+        if let Ok(mut injected) = self.injected.lock()
         {
-            return keys.contains(&key);
+            injected.push(frame, event);
         }
+    }
 
-        false
+    pub(crate) fn take_injected(&self, frame: u64) -> Vec<InputEvent>
+    {
+        if let Ok(mut injected) = self.injected.lock()
+        {
+            return injected.take_due(frame);
+        }
+
+        vec![]
+    }
+
+    pub(crate) fn pending_injections(&self) -> usize
+    {
+        self.injected.lock().map(|i| i.len()).unwrap_or(0)
     }
 
-    pub(crate) fn held_snapshot_contains(&self, key: V39Key) -> bool
+    pub(crate) fn set_axis(&self, axis: V39Axis, value: (f32, f32))
     {
-        if let Ok(keys) = self.snapshot_keys_held.lock()
+        if let Ok(mut axes) = self.axes.lock()
         {
-            return keys.contains(&key);
+            axes.insert(axis, value);
         }
+    }
 
-        false
+    pub(crate) fn axis(&self, axis: V39Axis) -> (f32, f32)
+    {
+        if let Ok(axes) = self.axes.lock()
+        {
+            return axes.get(&axis).copied().unwrap_or((0.0, 0.0));
+        }
+
+        (0.0, 0.0)
     }
 
-    pub(crate) fn clear_down_snapshot(&self)
+    pub(crate) fn add_gesture(&self, id: u32, gesture: Gesture)
     {
-        self.snapshot_keys_down
-            .lock()
-            .unwrap()
-            .clear();
+        if let Ok(mut gestures) = self.gestures.lock()
+        {
+            gestures.add(id, gesture);
+        }
     }
 
-    pub(crate) fn clear_up_snapshot(&self)
+    pub(crate) fn remove_gesture(&self, id: u32)
     {
-        self.snapshot_keys_down
-            .lock()
-            .unwrap()
-            .clear();
+        if let Ok(mut gestures) = self.gestures.lock()
+        {
+            gestures.remove(id);
+        }
     }
 
-    pub(crate) fn clear_held_snapshot(&self)
+    pub(crate) fn gesture_key_down(&self, key: V39Key, now: Duration) -> Vec<u32>
     {
-        self.snapshot_keys_up
-            .lock()
-            .unwrap()
-            .clear();
+        if let Ok(mut gestures) = self.gestures.lock()
+        {
+            return gestures.key_down(key, now);
+        }
+
+        vec![]
     }
 
-    pub(crate) fn down_held_up_conversion(&self)
+    pub(crate) fn gesture_key_up(&self, key: V39Key)
     {
-        let mut held = self.snapshot_keys_held.lock().unwrap();
-        let mut up = self.snapshot_keys_up.lock().unwrap();
-        let mut down = self.snapshot_keys_down.lock().unwrap();
- 
-        let mut new_held = vec![];
+        if let Ok(mut gestures) = self.gestures.lock()
+        {
+            gestures.key_up(key);
+        }
+    }
 
-        for key in down.iter()
+    pub(crate) fn update_gestures(&self, now: Duration) -> Vec<u32>
+    {
+        if let Ok(mut gestures) = self.gestures.lock()
         {
-            if !held.contains(key) 
-            {
-                new_held.push(*key)
-            }
+            return gestures.update(now);
         }
 
-        while let Some(key) = held.pop()
+        vec![]
+    }
+
+    pub(crate) fn push_context(&self, context: InputContext)
+    {
+        if let Ok(mut contexts) = self.contexts.lock()
         {
-            if !up.contains(&key)
-            {
-                new_held.push(key);
-            }
+            contexts.push(context);
+        }
+    }
+
+    pub(crate) fn pop_context(&self) -> Option<InputContext>
+    {
+        self.contexts.lock().ok()?.pop()
+    }
+
+    pub(crate) fn remove_context(&self, name: &str) -> Option<InputContext>
+    {
+        self.contexts.lock().ok()?.remove(name)
+    }
+
+    pub(crate) fn top_context(&self) -> Option<InputContext>
+    {
+        self.contexts.lock().ok()?.top().cloned()
+    }
+
+    pub(crate) fn context_active(&self, name: &str) -> bool
+    {
+        if let Ok(contexts) = self.contexts.lock()
+        {
+            return contexts.contains(name);
         }
 
-        *held = new_held;
-        up.clear()
+        false
+    }
+
+    pub(crate) fn key_visible(&self, key: V39Key, layer: InputLayer) -> bool
+    {
+        if self.key_captured(key, layer) {return false}
+
+        if let Ok(contexts) = self.contexts.lock()
+        {
+            return contexts.visible(key, layer);
+        }
+
+        true
+    }
+
+    /// Whether the key was taken by an input capture and is hidden from the context.
+    pub(crate) fn key_captured(&self, key: V39Key, layer: InputLayer) -> bool
+    {
+        layer != InputLayer::Raw && self.capture().swallows_key(key)
+    }
+
+    /// Whether mouse, pad and touch input reaches the layer, only `InputContext::consume_all` hides it.
+    pub(crate) fn input_visible(&self, layer: InputLayer) -> bool
+    {
+        if let Ok(contexts) = self.contexts.lock()
+        {
+            return contexts.input_visible(layer);
+        }
+
+        true
     }
-}
 
+    pub(crate) fn mouse_visible(&self, button: V39Mouse, layer: InputLayer) -> bool
+    {
+        !self.mouse_captured(button, layer) && self.input_visible(layer)
+    }
+
+    pub(crate) fn mouse_captured(&self, button: V39Mouse, layer: InputLayer) -> bool
+    {
+        layer != InputLayer::Raw && self.capture().swallows_mouse(button)
+    }
+
+    pub(crate) fn pad_visible(&self, button: V39Pad, layer: InputLayer) -> bool
+    {
+        !self.pad_captured(button, layer) && self.input_visible(layer)
+    }
+
+    pub(crate) fn pad_captured(&self, button: V39Pad, layer: InputLayer) -> bool
+    {
+        layer != InputLayer::Raw && self.capture().swallows_pad(button)
+    }
+
+    pub(crate) fn resolve_action(&self, action: &str) -> Option<(String, Vec<V39Key>)>
+    {
+        self.contexts.lock().ok()?.resolve_action(action)
+    }
+}
@@ -0,0 +1,273 @@
+use crate::input::V39Key;
+use crate::prelude::*;
+use std::ops::{BitOr, BitOrAssign, BitAnd};
+use std::fmt;
+
+
+/// Set of modifier keys, left and right variants of a modifier are not distinguished.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Default)]
+pub struct Modifiers(u8);
+
+
+impl Modifiers
+{
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const SHIFT: Modifiers = Modifiers(1);
+    pub const CTRL: Modifiers = Modifiers(1 << 1);
+    pub const ALT: Modifiers = Modifiers(1 << 2);
+    pub const SUPER: Modifiers = Modifiers(1 << 3);
+
+    #[inline]
+    pub fn contains(&self, other: Modifiers) -> bool
+    {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, other: Modifiers)
+    {
+        self.0 |= other.0;
+    }
+
+    #[inline]
+    pub fn remove(&mut self, other: Modifiers)
+    {
+        self.0 &= !other.0;
+    }
+
+    pub fn shift(&self) -> bool {self.contains(Self::SHIFT)}
+    pub fn ctrl(&self) -> bool {self.contains(Self::CTRL)}
+    pub fn alt(&self) -> bool {self.contains(Self::ALT)}
+    pub fn super_key(&self) -> bool {self.contains(Self::SUPER)}
+
+    /// The modifier a key stands for, `None` for ordinary keys.
+    pub fn from_key(key: V39Key) -> Option<Modifiers>
+    {
+        match key
+        {
+            V39Key::Shift | V39Key::RightShift => Some(Self::SHIFT),
+            V39Key::Ctrl | V39Key::RightCtrl => Some(Self::CTRL),
+            V39Key::Alt | V39Key::RightAlt => Some(Self::ALT),
+            V39Key::Super => Some(Self::SUPER),
+            _ => None,
+        }
+    }
+
+    pub fn is_modifier(key: V39Key) -> bool
+    {
+        Self::from_key(key).is_some()
+    }
+}
+
+
+impl BitOr for Modifiers
+{
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Self) -> Self::Output
+    {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+
+impl BitOrAssign for Modifiers
+{
+    fn bitor_assign(&mut self, rhs: Self)
+    {
+        self.0 |= rhs.0;
+    }
+}
+
+
+impl BitAnd for Modifiers
+{
+    type Output = Modifiers;
+
+    fn bitand(self, rhs: Self) -> Self::Output
+    {
+        Modifiers(self.0 & rhs.0)
+    }
+}
+
+
+impl fmt::Display for Modifiers
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let names = [(Self::CTRL, "Ctrl"), (Self::SHIFT, "Shift"), (Self::ALT, "Alt"), (Self::SUPER, "Super")];
+
+        let active = names.iter()
+            .filter(|(m, _)| self.contains(*m))
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", active.join("+"))
+    }
+}
+
+
+impl fmt::Debug for Modifiers
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "Modifiers({self})")
+    }
+}
+
+
+/// A key combined with an exact set of modifiers, e.g. Ctrl+Shift+Z.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Shortcut
+{
+    pub modifiers: Modifiers,
+    pub key: V39Key,
+}
+
+
+impl Shortcut
+{
+    pub fn new(modifiers: Modifiers, key: V39Key) -> Self
+    {
+        Self {modifiers, key}
+    }
+
+    /// Parses shortcuts like `"Ctrl+Shift+Z"` or `"Alt+Enter"`.
+    /// Names are case insensitive and the last part has to be the key.
+    /// The plus key is written as `"+"`, `"Ctrl++"` or `"Ctrl + +"`.
+    pub fn parse(text: &str) -> V39Result<Self>
+    {
+        let invalid = || V39Error::InvalidShortcut(text.into());
+
+        let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+
+        // A trailing plus key leaves two empty parts behind
+        if parts.len() >= 2 && parts.ends_with(&["", ""])
+        {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+
+        let (key, mods) = parts.split_last().ok_or_else(invalid)?;
+        let key = key.parse::<V39Key>().map_err(|_| invalid())?;
+        let mut modifiers = Modifiers::NONE;
+
+        for name in mods
+        {
+            modifiers |= match name.to_lowercase().as_str()
+            {
+                "shift" => Modifiers::SHIFT,
+                "ctrl" | "control" => Modifiers::CTRL,
+                "alt" | "option" => Modifiers::ALT,
+                "super" | "cmd" | "command" | "meta" | "win" => Modifiers::SUPER,
+                _ => return Err(invalid()),
+            };
+        }
+
+        Ok(Self {modifiers, key})
+    }
+
+    /// Whether the key pressed with the given modifiers triggers this shortcut.
+    /// Modifiers have to match exactly, Ctrl+Z does not trigger on Ctrl+Shift+Z.
+    pub fn matches(&self, key: V39Key, modifiers: Modifiers) -> bool
+    {
+        self.key == key && self.modifiers == modifiers
+    }
+}
+
+
+impl fmt::Display for Shortcut
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        if self.modifiers.is_empty() {write!(f, "{:?}", self.key)}
+        else {write!(f, "{}+{:?}", self.modifiers, self.key)}
+    }
+}
+
+
+impl std::str::FromStr for Shortcut
+{
+    type Err = V39Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        Self::parse(s)
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn parse(text: &str) -> Shortcut
+    {
+        Shortcut::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_modifiers_and_key()
+    {
+        assert_eq!(parse("Ctrl+Shift+Z"), Shortcut::new(Modifiers::CTRL | Modifiers::SHIFT, V39Key::Z));
+        assert_eq!(parse("shift + ctrl + z"), Shortcut::new(Modifiers::CTRL | Modifiers::SHIFT, V39Key::Z));
+        assert_eq!(parse("Alt+Enter"), Shortcut::new(Modifiers::ALT, V39Key::Enter));
+        assert_eq!(parse("Cmd+4"), Shortcut::new(Modifiers::SUPER, V39Key::D4));
+        assert_eq!(parse("F4"), Shortcut::new(Modifiers::NONE, V39Key::F4));
+    }
+
+    #[test]
+    fn parses_the_plus_key()
+    {
+        assert_eq!(parse("+"), Shortcut::new(Modifiers::NONE, V39Key::Plus));
+        assert_eq!(parse("Ctrl++"), Shortcut::new(Modifiers::CTRL, V39Key::Plus));
+        assert_eq!(parse("Ctrl + +"), Shortcut::new(Modifiers::CTRL, V39Key::Plus));
+        assert_eq!(parse("Ctrl+Plus"), Shortcut::new(Modifiers::CTRL, V39Key::Plus));
+    }
+
+    #[test]
+    fn rejects_invalid_shortcuts()
+    {
+        for text in ["", "Ctrl+", "Ctrl++Z", "Hyper+Z", "Ctrl+NoSuchKey", "Z+Ctrl"]
+        {
+            assert!(Shortcut::parse(text).is_err(), "{text:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn display_parses_back()
+    {
+        for text in ["Ctrl+Shift+Z", "Ctrl+Alt+Super+Delete", "Ctrl++", "Esc"]
+        {
+            let shortcut = parse(text);
+            assert_eq!(parse(&shortcut.to_string()), shortcut);
+        }
+    }
+
+    #[test]
+    fn matches_exact_modifiers()
+    {
+        let redo = parse("Ctrl+Shift+Z");
+
+        assert!(redo.matches(V39Key::Z, Modifiers::CTRL | Modifiers::SHIFT));
+        assert!(!redo.matches(V39Key::Z, Modifiers::CTRL));
+        assert!(!parse("Ctrl+Z").matches(V39Key::Z, Modifiers::CTRL | Modifiers::SHIFT));
+        assert!(!redo.matches(V39Key::Y, Modifiers::CTRL | Modifiers::SHIFT));
+    }
+
+    #[test]
+    fn modifier_keys()
+    {
+        assert_eq!(Modifiers::from_key(V39Key::RightCtrl), Some(Modifiers::CTRL));
+        assert_eq!(Modifiers::from_key(V39Key::Z), None);
+        assert!((Modifiers::SUPER | Modifiers::ALT).super_key());
+        assert_eq!((Modifiers::SUPER | Modifiers::CTRL | Modifiers::SHIFT).to_string(), "Ctrl+Shift+Super");
+    }
+}
@@ -0,0 +1,181 @@
+use crate::input::V39Key;
+use std::collections::HashMap;
+use std::time::Duration;
+
+
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub enum KeyRepeat
+{
+    /// Held keys never repeat.
+    #[default]
+    Off,
+
+    /// Repeats follow the delay and rate configured in the operating system.
+    Os,
+
+    /// Repeats are generated by the engine, independent of the system settings.
+    Engine {delay: Duration, rate: Duration},
+}
+
+
+impl KeyRepeat
+{
+    /// Engine side repeat with the common defaults of 500 ms delay and 30 repeats per second.
+    pub fn engine() -> Self
+    {
+        KeyRepeat::Engine {
+            delay: Duration::from_millis(500),
+            rate: Duration::from_secs(1) / 30,
+        }
+    }
+}
+
+
+/// Keeps track of when each held key is due for its next engine side repeat.
+#[derive(Default)]
+pub(crate) struct RepeatTracker
+{
+    held: HashMap<V39Key, (Duration, Option<Duration>)>,
+}
+
+
+impl RepeatTracker
+{
+    pub(crate) fn press(&mut self, key: V39Key, now: Duration)
+    {
+        self.held.entry(key).or_insert((now, None));
+    }
+
+    pub(crate) fn release(&mut self, key: V39Key)
+    {
+        self.held.remove(&key);
+    }
+
+    pub(crate) fn release_all(&mut self)
+    {
+        self.held.clear();
+    }
+
+    /// Returns the keys which have to repeat at `now`, a key repeats at most once per call.
+    pub(crate) fn due(&mut self, now: Duration, mode: impl Fn(V39Key) -> KeyRepeat) -> Vec<V39Key>
+    {
+        let mut due = vec![];
+
+        for (key, (pressed, next)) in self.held.iter_mut()
+        {
+            let KeyRepeat::Engine {delay, rate} = mode(*key) else {
+                *next = None;
+                continue;
+            };
+
+            let at = next.get_or_insert(*pressed + delay);
+
+            if now >= *at
+            {
+                // Frames slower than the rate drop repeats instead of bursting them
+                *at += rate.max(Duration::from_millis(1));
+                if *at <= now {*at = now + rate}
+
+                due.push(*key);
+            }
+        }
+
+        due
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::input::context::{ContextStack, InputContext};
+
+    fn ms(ms: u64) -> Duration
+    {
+        Duration::from_millis(ms)
+    }
+
+    fn engine(delay: u64, rate: u64) -> KeyRepeat
+    {
+        KeyRepeat::Engine {delay: ms(delay), rate: ms(rate)}
+    }
+
+    #[test]
+    fn repeats_after_the_delay_at_the_rate()
+    {
+        let mut tracker = RepeatTracker::default();
+        tracker.press(V39Key::Backspace, ms(0));
+
+        let mode = |_| engine(500, 100);
+
+        assert!(tracker.due(ms(499), mode).is_empty());
+        assert_eq!(tracker.due(ms(500), mode), [V39Key::Backspace]);
+        assert!(tracker.due(ms(550), mode).is_empty());
+        assert_eq!(tracker.due(ms(600), mode), [V39Key::Backspace]);
+
+        tracker.release(V39Key::Backspace);
+        assert!(tracker.due(ms(700), mode).is_empty());
+    }
+
+    #[test]
+    fn slow_frames_do_not_burst()
+    {
+        let mut tracker = RepeatTracker::default();
+        tracker.press(V39Key::Left, ms(0));
+
+        let mode = |_| engine(100, 10);
+
+        assert_eq!(tracker.due(ms(500), mode), [V39Key::Left]);
+        assert!(tracker.due(ms(505), mode).is_empty());
+        assert_eq!(tracker.due(ms(510), mode), [V39Key::Left]);
+    }
+
+    #[test]
+    fn off_and_os_are_not_repeated_by_the_engine()
+    {
+        let mut tracker = RepeatTracker::default();
+        tracker.press(V39Key::A, ms(0));
+
+        assert!(tracker.due(ms(1000), |_| KeyRepeat::Off).is_empty());
+        assert!(tracker.due(ms(2000), |_| KeyRepeat::Os).is_empty());
+
+        // Switching to engine repeat counts the delay from the press
+        assert_eq!(tracker.due(ms(2000), |_| KeyRepeat::engine()), [V39Key::A]);
+    }
+
+    #[test]
+    fn contexts_and_actions_override_the_repeat()
+    {
+        let mut stack = ContextStack::default();
+        stack.push(InputContext::new("gameplay").repeat(engine(100, 50)));
+
+        stack.push(InputContext::new("menu")
+            .bind("scroll", V39Key::Down)
+            .bind("confirm", V39Key::Enter)
+            .repeat(engine(300, 100))
+            .repeat_action("confirm", KeyRepeat::Off)
+            .consume_action("confirm"));
+
+        let mut tracker = RepeatTracker::default();
+        let mode = |key| stack.repeat_for(key).unwrap_or(KeyRepeat::Off);
+
+        for key in [V39Key::Down, V39Key::Enter, V39Key::W]
+        {
+            tracker.press(key, ms(0));
+        }
+
+        // The topmost context which configures a repeat wins, a consumed key stops the search
+        assert!(tracker.due(ms(200), mode).is_empty());
+
+        let mut due = tracker.due(ms(300), mode);
+        due.sort_by_key(|key| *key as usize);
+        assert_eq!(due, [V39Key::W, V39Key::Down]);
+
+        assert_eq!(stack.repeat_for(V39Key::Enter), Some(KeyRepeat::Off));
+        assert_eq!(stack.repeat_for(V39Key::W), Some(engine(300, 100)));
+
+        stack.pop();
+        assert_eq!(stack.repeat_for(V39Key::W), Some(engine(100, 50)));
+    }
+}
@@ -0,0 +1,343 @@
+use crate::input::{V39Key, V39Mouse, V39Pad};
+use std::time::Duration;
+
+
+/// Anything that can be pressed and released, mapped to a dense index for bitset storage.
+pub(crate) trait InputCode: Copy
+{
+    const COUNT: usize;
+    fn index(self) -> usize;
+}
+
+
+impl InputCode for V39Key
+{
+    const COUNT: usize = V39Key::ß as usize + 1;
+
+    #[inline]
+    fn index(self) -> usize
+    {
+        self as usize
+    }
+}
+
+
+impl InputCode for V39Mouse
+{
+    const COUNT: usize = V39Mouse::Forward as usize + 1;
+
+    #[inline]
+    fn index(self) -> usize
+    {
+        self as usize
+    }
+}
+
+
+impl InputCode for V39Pad
+{
+    const COUNT: usize = V39Pad::BumberRight as usize + 1;
+
+    #[inline]
+    fn index(self) -> usize
+    {
+        self as usize
+    }
+}
+
+
+const WORDS: usize = 2;
+
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct BitSet([u64; WORDS]);
+
+
+impl BitSet
+{
+    pub(crate) const CAPACITY: usize = WORDS * 64;
+
+    #[inline]
+    pub(crate) fn insert(&mut self, index: usize)
+    {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    #[inline]
+    pub(crate) fn remove(&mut self, index: usize)
+    {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, index: usize) -> bool
+    {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self)
+    {
+        self.0 = [0; WORDS];
+    }
+
+    pub(crate) fn is_empty(&self) -> bool
+    {
+        self.0.iter().all(|w| *w == 0)
+    }
+}
+
+
+const _: () = assert!(V39Key::COUNT <= BitSet::CAPACITY);
+const _: () = assert!(V39Mouse::COUNT <= BitSet::CAPACITY);
+const _: () = assert!(V39Pad::COUNT <= BitSet::CAPACITY);
+
+
+/// Frame accurate state of a set of buttons.
+///
+/// `pressed` and `released` only contain edges of the current frame and are reset by `begin_frame`.
+/// A button pressed and released within a single frame is both pressed and released, but not held.
+pub(crate) struct ButtonState<T: InputCode>
+{
+    held: BitSet,
+    pressed: BitSet,
+    released: BitSet,
+    pressed_at: Vec<Option<Duration>>,
+    _code: std::marker::PhantomData<T>,
+}
+
+
+impl<T: InputCode> Default for ButtonState<T>
+{
+    fn default() -> Self
+    {
+        Self {
+            held: BitSet::default(),
+            pressed: BitSet::default(),
+            released: BitSet::default(),
+            pressed_at: vec![None; T::COUNT],
+            _code: std::marker::PhantomData,
+        }
+    }
+}
+
+
+impl<T: InputCode> ButtonState<T>
+{
+    /// Forgets the edges of the previous frame, the held state carries over.
+    pub(crate) fn begin_frame(&mut self)
+    {
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    pub(crate) fn press(&mut self, code: T, now: Duration)
+    {
+        let index = code.index();
+
+        // Repeated presses without a release in between are ignored
+        if self.held.contains(index) {return}
+
+        self.held.insert(index);
+        self.pressed.insert(index);
+        self.pressed_at[index] = Some(now);
+    }
+
+    pub(crate) fn release(&mut self, code: T)
+    {
+        let index = code.index();
+
+        if !self.held.contains(index) {return}
+
+        self.held.remove(index);
+        self.released.insert(index);
+        self.pressed_at[index] = None;
+    }
+
+    /// The codes out of `codes` which are held.
+    pub(crate) fn held(&self, codes: &[T]) -> Vec<T>
+    {
+        codes.iter().copied().filter(|code| self.is_held(*code)).collect()
+    }
+
+    /// Releases everything which is held, e.g. when the window loses focus.
+    pub(crate) fn release_all(&mut self)
+    {
+        for index in 0..T::COUNT
+        {
+            if self.held.contains(index)
+            {
+                self.held.remove(index);
+                self.released.insert(index);
+                self.pressed_at[index] = None;
+            }
+        }
+    }
+
+    /// Pressed during the current frame.
+    pub(crate) fn is_down(&self, code: T) -> bool
+    {
+        self.pressed.contains(code.index())
+    }
+
+    /// Released during the current frame.
+    pub(crate) fn is_up(&self, code: T) -> bool
+    {
+        self.released.contains(code.index())
+    }
+
+    /// Currently held down, this includes the frame it was pressed in.
+    pub(crate) fn is_held(&self, code: T) -> bool
+    {
+        self.held.contains(code.index())
+    }
+
+    pub(crate) fn any_held(&self) -> bool
+    {
+        !self.held.is_empty()
+    }
+
+    pub(crate) fn pressed_at(&self, code: T) -> Option<Duration>
+    {
+        self.pressed_at[code.index()]
+    }
+
+    pub(crate) fn held_duration(&self, code: T, now: Duration) -> Option<Duration>
+    {
+        self.pressed_at(code).map(|at| now.saturating_sub(at))
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ms(ms: u64) -> Duration
+    {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn press_is_down_and_held_for_one_frame()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+
+        keys.begin_frame();
+        keys.press(V39Key::A, ms(0));
+
+        assert!(keys.is_down(V39Key::A));
+        assert!(keys.is_held(V39Key::A));
+        assert!(!keys.is_up(V39Key::A));
+
+        keys.begin_frame();
+
+        assert!(!keys.is_down(V39Key::A));
+        assert!(keys.is_held(V39Key::A));
+    }
+
+    #[test]
+    fn release_is_up_for_one_frame()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+
+        keys.press(V39Key::W, ms(0));
+        keys.begin_frame();
+        keys.release(V39Key::W);
+
+        assert!(keys.is_up(V39Key::W));
+        assert!(!keys.is_held(V39Key::W));
+        assert!(!keys.is_down(V39Key::W));
+
+        keys.begin_frame();
+        assert!(!keys.is_up(V39Key::W));
+    }
+
+    #[test]
+    fn press_and_release_within_one_frame()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+
+        keys.begin_frame();
+        keys.press(V39Key::Space, ms(5));
+        keys.release(V39Key::Space);
+
+        assert!(keys.is_down(V39Key::Space));
+        assert!(keys.is_up(V39Key::Space));
+        assert!(!keys.is_held(V39Key::Space));
+        assert_eq!(keys.pressed_at(V39Key::Space), None);
+
+        keys.begin_frame();
+
+        assert!(!keys.is_down(V39Key::Space));
+        assert!(!keys.is_up(V39Key::Space));
+    }
+
+    #[test]
+    fn release_and_press_within_one_frame()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+
+        keys.press(V39Key::D, ms(0));
+        keys.begin_frame();
+        keys.release(V39Key::D);
+        keys.press(V39Key::D, ms(20));
+
+        assert!(keys.is_up(V39Key::D));
+        assert!(keys.is_down(V39Key::D));
+        assert!(keys.is_held(V39Key::D));
+        assert_eq!(keys.pressed_at(V39Key::D), Some(ms(20)));
+    }
+
+    #[test]
+    fn repeated_press_keeps_first_timestamp()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+
+        keys.press(V39Key::Enter, ms(10));
+        keys.begin_frame();
+        keys.press(V39Key::Enter, ms(50));
+
+        assert!(!keys.is_down(V39Key::Enter));
+        assert_eq!(keys.pressed_at(V39Key::Enter), Some(ms(10)));
+        assert_eq!(keys.held_duration(V39Key::Enter, ms(110)), Some(ms(100)));
+    }
+
+    #[test]
+    fn release_without_press_is_ignored()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+        keys.release(V39Key::Q);
+
+        assert!(!keys.is_up(V39Key::Q));
+        assert_eq!(keys.held_duration(V39Key::Q, ms(10)), None);
+    }
+
+    #[test]
+    fn keys_do_not_alias()
+    {
+        let mut keys = ButtonState::<V39Key>::default();
+        keys.press(V39Key::ß, ms(0));
+
+        assert!(keys.is_held(V39Key::ß));
+        assert!(!keys.is_held(V39Key::A));
+        assert!(!keys.is_held(V39Key::Ä));
+    }
+
+    #[test]
+    fn release_all_produces_up_edges()
+    {
+        let mut buttons = ButtonState::<V39Mouse>::default();
+        buttons.press(V39Mouse::Left, ms(0));
+        buttons.press(V39Mouse::Right, ms(0));
+        buttons.begin_frame();
+
+        assert_eq!(buttons.held(V39Mouse::ALL), [V39Mouse::Left, V39Mouse::Right]);
+        buttons.release_all();
+
+        assert!(buttons.is_up(V39Mouse::Left));
+        assert!(buttons.is_up(V39Mouse::Right));
+        assert!(!buttons.any_held());
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+
+/// Distance in physical pixels a touch may travel before it no longer counts as a tap.
+const TAP_SLOP: f64 = 10.0;
+
+/// Longest press which still counts as a tap.
+const TAP_TIME: Duration = Duration::from_millis(250);
+
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TouchPhase
+{
+    Started,
+    Moved,
+    Ended,
+    Cancelled,
+}
+
+
+/// A single finger on the screen.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Touch
+{
+    /// Unique while the finger stays on the screen, ids may be reused afterwards.
+    pub id: u64,
+    pub phase: TouchPhase,
+
+    /// Position in physical pixels relative to the top left corner of the window.
+    pub position: (f64, f64),
+
+    /// Normalized pressure from 0 to 1, `None` if the device does not report it.
+    pub pressure: Option<f32>,
+}
+
+
+impl Touch
+{
+    pub fn new(id: u64, phase: TouchPhase, position: (f64, f64)) -> Self
+    {
+        Self {id, phase, position, pressure: None}
+    }
+
+    pub fn with_pressure(mut self, pressure: f32) -> Self
+    {
+        self.pressure = Some(pressure);
+        self
+    }
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TouchGesture
+{
+    /// A short touch of a single finger which barely moved.
+    Tap {position: (f64, f64)},
+
+    /// A single finger moved by `delta` since the last pan.
+    Pan {position: (f64, f64), delta: (f64, f64)},
+
+    /// Two fingers moved apart (`scale` > 1) or together (`scale` < 1) since the last pinch.
+    Pinch {center: (f64, f64), scale: f64},
+
+    /// Two fingers rotated by `angle` radians since the last rotation, clockwise is positive.
+    Rotate {center: (f64, f64), angle: f64},
+}
+
+
+#[derive(Copy, Clone, Debug)]
+struct ActiveTouch
+{
+    touch: Touch,
+    start: (f64, f64),
+    started_at: Duration,
+    panning: bool,
+    tap: bool,
+}
+
+
+/// Tracks the fingers on the screen and recognizes gestures from their movement.
+#[derive(Default)]
+pub(crate) struct TouchTracker
+{
+    active: HashMap<u64, ActiveTouch>,
+}
+
+
+impl TouchTracker
+{
+    pub(crate) fn touches(&self) -> Vec<Touch>
+    {
+        let mut touches = self.active.values().map(|t| t.touch).collect::<Vec<_>>();
+        touches.sort_by_key(|t| t.id);
+        touches
+    }
+
+    pub(crate) fn touch(&self, id: u64) -> Option<Touch>
+    {
+        self.active.get(&id).map(|t| t.touch)
+    }
+
+    pub(crate) fn clear(&mut self)
+    {
+        self.active.clear();
+    }
+
+    /// Updates the tracked fingers, returns the gestures the touch completes or continues.
+    pub(crate) fn update(&mut self, touch: Touch, now: Duration) -> Vec<TouchGesture>
+    {
+        match touch.phase
+        {
+            TouchPhase::Started => self.start(touch, now),
+            TouchPhase::Moved => self.moved(touch),
+            TouchPhase::Ended => self.end(touch, now),
+
+            TouchPhase::Cancelled => {
+                self.remove(touch.id);
+                vec![]
+            },
+        }
+    }
+
+    fn start(&mut self, touch: Touch, now: Duration) -> Vec<TouchGesture>
+    {
+        // A second finger turns every touch into a multi touch gesture
+        let alone = self.active.is_empty();
+
+        for other in self.active.values_mut()
+        {
+            other.tap = false;
+            other.panning = false;
+        }
+
+        self.active.insert(touch.id, ActiveTouch {
+            touch,
+            start: touch.position,
+            started_at: now,
+            panning: false,
+            tap: alone,
+        });
+
+        vec![]
+    }
+
+    fn moved(&mut self, touch: Touch) -> Vec<TouchGesture>
+    {
+        let pair = self.pair_positions();
+
+        let Some(active) = self.active.get_mut(&touch.id) else {return vec![]};
+        let last = active.touch.position;
+        active.touch = touch;
+
+        if distance(active.start, touch.position) > TAP_SLOP
+        {
+            active.tap = false;
+        }
+
+        match self.active.len()
+        {
+            1 => self.pan(touch, last),
+            2 => two_finger(pair, self.pair_positions()),
+            _ => vec![],
+        }
+    }
+
+    fn pan(&mut self, touch: Touch, last: (f64, f64)) -> Vec<TouchGesture>
+    {
+        let Some(active) = self.active.get_mut(&touch.id) else {return vec![]};
+
+        if !active.panning
+        {
+            if distance(active.start, touch.position) <= TAP_SLOP {return vec![]}
+
+            // The first pan covers the distance travelled within the slop
+            active.panning = true;
+            let delta = (touch.position.0 - active.start.0, touch.position.1 - active.start.1);
+            return vec![TouchGesture::Pan {position: touch.position, delta}];
+        }
+
+        let delta = (touch.position.0 - last.0, touch.position.1 - last.1);
+        vec![TouchGesture::Pan {position: touch.position, delta}]
+    }
+
+    fn end(&mut self, touch: Touch, now: Duration) -> Vec<TouchGesture>
+    {
+        let Some(active) = self.remove(touch.id) else {return vec![]};
+
+        let tap = active.tap
+            && now.saturating_sub(active.started_at) <= TAP_TIME
+            && distance(active.start, touch.position) <= TAP_SLOP;
+
+        if tap {vec![TouchGesture::Tap {position: touch.position}]}
+        else {vec![]}
+    }
+
+    fn remove(&mut self, id: u64) -> Option<ActiveTouch>
+    {
+        let removed = self.active.remove(&id)?;
+
+        // The finger left over from a two finger gesture starts panning from where it is
+        for other in self.active.values_mut()
+        {
+            other.start = other.touch.position;
+        }
+
+        Some(removed)
+    }
+
+    /// Positions of the two fingers ordered by id, `None` unless exactly two are down.
+    fn pair_positions(&self) -> Option<((f64, f64), (f64, f64))>
+    {
+        if self.active.len() != 2 {return None}
+
+        let touches = self.touches();
+        Some((touches[0].position, touches[1].position))
+    }
+}
+
+
+fn two_finger(before: Option<((f64, f64), (f64, f64))>, after: Option<((f64, f64), (f64, f64))>) -> Vec<TouchGesture>
+{
+    let (Some((a0, b0)), Some((a1, b1))) = (before, after) else {return vec![]};
+
+    let center = ((a1.0 + b1.0) / 2.0, (a1.1 + b1.1) / 2.0);
+    let mut gestures = vec![];
+
+    let (d0, d1) = (distance(a0, b0), distance(a1, b1));
+
+    if d0 > 0.0 && d1 != d0
+    {
+        gestures.push(TouchGesture::Pinch {center, scale: d1 / d0});
+    }
+
+    let angle = normalize_angle(angle(a1, b1) - angle(a0, b0));
+
+    if angle != 0.0
+    {
+        gestures.push(TouchGesture::Rotate {center, angle});
+    }
+
+    gestures
+}
+
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64
+{
+    (b.0 - a.0).hypot(b.1 - a.1)
+}
+
+
+fn angle(a: (f64, f64), b: (f64, f64)) -> f64
+{
+    (b.1 - a.1).atan2(b.0 - a.0)
+}
+
+
+/// Maps the angle into (-pi, pi], so crossing the atan2 seam does not report a full turn.
+fn normalize_angle(angle: f64) -> f64
+{
+    use std::f64::consts::{PI, TAU};
+
+    let angle = angle.rem_euclid(TAU);
+    if angle > PI {angle - TAU} else {angle}
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ms(ms: u64) -> Duration
+    {
+        Duration::from_millis(ms)
+    }
+
+    fn touch(id: u64, phase: TouchPhase, position: (f64, f64)) -> Touch
+    {
+        Touch::new(id, phase, position)
+    }
+
+    #[test]
+    fn short_touch_is_a_tap()
+    {
+        let mut tracker = TouchTracker::default();
+
+        tracker.update(touch(0, TouchPhase::Started, (100.0, 100.0)), ms(0));
+        tracker.update(touch(0, TouchPhase::Moved, (103.0, 101.0)), ms(50));
+        let gestures = tracker.update(touch(0, TouchPhase::Ended, (103.0, 101.0)), ms(100));
+
+        assert_eq!(gestures, vec![TouchGesture::Tap {position: (103.0, 101.0)}]);
+        assert!(tracker.touches().is_empty());
+    }
+
+    #[test]
+    fn long_touch_is_no_tap()
+    {
+        let mut tracker = TouchTracker::default();
+
+        tracker.update(touch(0, TouchPhase::Started, (0.0, 0.0)), ms(0));
+        let gestures = tracker.update(touch(0, TouchPhase::Ended, (0.0, 0.0)), ms(400));
+
+        assert!(gestures.is_empty());
+    }
+
+    #[test]
+    fn drag_pans_instead_of_tapping()
+    {
+        let mut tracker = TouchTracker::default();
+
+        tracker.update(touch(0, TouchPhase::Started, (0.0, 0.0)), ms(0));
+        assert!(tracker.update(touch(0, TouchPhase::Moved, (5.0, 0.0)), ms(10)).is_empty());
+
+        let first = tracker.update(touch(0, TouchPhase::Moved, (20.0, 0.0)), ms(20));
+        assert_eq!(first, vec![TouchGesture::Pan {position: (20.0, 0.0), delta: (20.0, 0.0)}]);
+
+        let second = tracker.update(touch(0, TouchPhase::Moved, (20.0, 15.0)), ms(30));
+        assert_eq!(second, vec![TouchGesture::Pan {position: (20.0, 15.0), delta: (0.0, 15.0)}]);
+
+        assert!(tracker.update(touch(0, TouchPhase::Ended, (20.0, 15.0)), ms(40)).is_empty());
+    }
+
+    #[test]
+    fn spreading_two_fingers_pinches()
+    {
+        let mut tracker = TouchTracker::default();
+
+        tracker.update(touch(0, TouchPhase::Started, (0.0, 0.0)), ms(0));
+        tracker.update(touch(1, TouchPhase::Started, (100.0, 0.0)), ms(0));
+        let gestures = tracker.update(touch(1, TouchPhase::Moved, (200.0, 0.0)), ms(10));
+
+        assert_eq!(gestures, vec![TouchGesture::Pinch {center: (100.0, 0.0), scale: 2.0}]);
+
+        // Neither finger is a tap anymore
+        assert!(tracker.update(touch(0, TouchPhase::Ended, (0.0, 0.0)), ms(20)).is_empty());
+        assert!(tracker.update(touch(1, TouchPhase::Ended, (200.0, 0.0)), ms(20)).is_empty());
+    }
+
+    #[test]
+    fn turning_two_fingers_rotates()
+    {
+        let mut tracker = TouchTracker::default();
+
+        tracker.update(touch(0, TouchPhase::Started, (0.0, 0.0)), ms(0));
+        tracker.update(touch(1, TouchPhase::Started, (100.0, 0.0)), ms(0));
+        let gestures = tracker.update(touch(1, TouchPhase::Moved, (0.0, 100.0)), ms(10));
+
+        let Some(TouchGesture::Rotate {angle, ..}) = gestures.last() else {panic!("no rotation in {gestures:?}")};
+        assert!((angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cancelled_touch_is_forgotten()
+    {
+        let mut tracker = TouchTracker::default();
+
+        tracker.update(touch(3, TouchPhase::Started, (0.0, 0.0)).with_pressure(0.5), ms(0));
+        assert_eq!(tracker.touch(3).and_then(|t| t.pressure), Some(0.5));
+
+        assert!(tracker.update(touch(3, TouchPhase::Cancelled, (0.0, 0.0)), ms(10)).is_empty());
+        assert_eq!(tracker.touch(3), None);
+    }
+}
@@ -1,16 +1,126 @@
-use crate::input::V39Key;
-use winit::keyboard::KeyCode;
+use crate::input::{V39Key, V39Mouse, Modifiers, Touch, TouchPhase};
+use winit::keyboard::{KeyCode, ModifiersState};
+use winit::event::MouseButton;
 
 
-pub fn winit_key_to_v39_key(key: &KeyCode) -> V39Key
+/// Keys are translated by their position on a US layout, `None` for keys without a V39Key.
+pub fn winit_key_to_v39_key(key: &KeyCode) -> Option<V39Key>
 {
-    //TODO: Complete this...
+    let key = match key
+    {
+        KeyCode::KeyA => V39Key::A, KeyCode::KeyB => V39Key::B, KeyCode::KeyC => V39Key::C,
+        KeyCode::KeyD => V39Key::D, KeyCode::KeyE => V39Key::E, KeyCode::KeyF => V39Key::F,
+        KeyCode::KeyG => V39Key::G, KeyCode::KeyH => V39Key::H, KeyCode::KeyI => V39Key::I,
+        KeyCode::KeyJ => V39Key::J, KeyCode::KeyK => V39Key::K, KeyCode::KeyL => V39Key::L,
+        KeyCode::KeyM => V39Key::M, KeyCode::KeyN => V39Key::N, KeyCode::KeyO => V39Key::O,
+        KeyCode::KeyP => V39Key::P, KeyCode::KeyQ => V39Key::Q, KeyCode::KeyR => V39Key::R,
+        KeyCode::KeyS => V39Key::S, KeyCode::KeyT => V39Key::T, KeyCode::KeyU => V39Key::U,
+        KeyCode::KeyV => V39Key::V, KeyCode::KeyW => V39Key::W, KeyCode::KeyX => V39Key::X,
+        KeyCode::KeyY => V39Key::Y, KeyCode::KeyZ => V39Key::Z,
+
+        KeyCode::Digit0 | KeyCode::Numpad0 => V39Key::D0,
+        KeyCode::Digit1 | KeyCode::Numpad1 => V39Key::D1,
+        KeyCode::Digit2 | KeyCode::Numpad2 => V39Key::D2,
+        KeyCode::Digit3 | KeyCode::Numpad3 => V39Key::D3,
+        KeyCode::Digit4 | KeyCode::Numpad4 => V39Key::D4,
+        KeyCode::Digit5 | KeyCode::Numpad5 => V39Key::D5,
+        KeyCode::Digit6 | KeyCode::Numpad6 => V39Key::D6,
+        KeyCode::Digit7 | KeyCode::Numpad7 => V39Key::D7,
+        KeyCode::Digit8 | KeyCode::Numpad8 => V39Key::D8,
+        KeyCode::Digit9 | KeyCode::Numpad9 => V39Key::D9,
+
+        KeyCode::ShiftLeft => V39Key::Shift,
+        KeyCode::ShiftRight => V39Key::RightShift,
+        KeyCode::ControlLeft => V39Key::Ctrl,
+        KeyCode::ControlRight => V39Key::RightCtrl,
+        KeyCode::AltLeft => V39Key::Alt,
+        KeyCode::AltRight => V39Key::RightAlt,
+        KeyCode::SuperLeft | KeyCode::SuperRight => V39Key::Super,
+
+        KeyCode::Space => V39Key::Space,
+        KeyCode::Tab => V39Key::Tab,
+        KeyCode::CapsLock => V39Key::Caps,
+        KeyCode::Escape => V39Key::Esc,
+        KeyCode::Backspace => V39Key::Backspace,
+        KeyCode::Enter | KeyCode::NumpadEnter => V39Key::Enter,
+
+        KeyCode::F1 => V39Key::F1, KeyCode::F2 => V39Key::F2, KeyCode::F3 => V39Key::F3,
+        KeyCode::F4 => V39Key::F4, KeyCode::F5 => V39Key::F5, KeyCode::F6 => V39Key::F6,
+        KeyCode::F7 => V39Key::F7, KeyCode::F8 => V39Key::F8, KeyCode::F9 => V39Key::F9,
+        KeyCode::F10 => V39Key::F10, KeyCode::F11 => V39Key::F11, KeyCode::F12 => V39Key::F12,
+
+        KeyCode::PrintScreen => V39Key::PrtScn,
+        KeyCode::Pause => V39Key::Pause,
+        KeyCode::ScrollLock => V39Key::ScrLck,
+        KeyCode::Insert => V39Key::Insert,
+        KeyCode::Delete => V39Key::Delete,
+        KeyCode::Home => V39Key::Home,
+        KeyCode::End => V39Key::End,
+        KeyCode::PageUp => V39Key::Pageup,
+        KeyCode::PageDown => V39Key::Pagedown,
+        KeyCode::ArrowLeft => V39Key::Left,
+        KeyCode::ArrowRight => V39Key::Right,
+        KeyCode::ArrowUp => V39Key::Up,
+        KeyCode::ArrowDown => V39Key::Down,
+
+        KeyCode::Comma => V39Key::Comma,
+        KeyCode::Period | KeyCode::NumpadDecimal => V39Key::Dot,
+        KeyCode::Semicolon => V39Key::Semicolon,
+        KeyCode::Quote => V39Key::SingleQuote,
+        KeyCode::Minus | KeyCode::NumpadSubtract => V39Key::Dash,
+        KeyCode::Equal | KeyCode::NumpadEqual => V39Key::Equal,
+        KeyCode::NumpadAdd => V39Key::Plus,
+        KeyCode::NumpadMultiply => V39Key::Star,
+        KeyCode::Backquote => V39Key::Tilde,
 
-    match key
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+
+pub fn winit_mouse_to_v39_mouse(button: &MouseButton) -> Option<V39Mouse>
+{
+    match button
     {
-        KeyCode::KeyA => V39Key::A,
-        KeyCode::KeyQ => V39Key::Q,
-        KeyCode::KeyF => V39Key::F,
-        _ => V39Key::Space,
+        MouseButton::Left => Some(V39Mouse::Left),
+        MouseButton::Right => Some(V39Mouse::Right),
+        MouseButton::Middle => Some(V39Mouse::Middle),
+        MouseButton::Back => Some(V39Mouse::Back),
+        MouseButton::Forward => Some(V39Mouse::Forward),
+        MouseButton::Other(_) => None,
+    }
+}
+
+
+pub fn winit_modifiers_to_v39_modifiers(state: &ModifiersState) -> Modifiers
+{
+    let mut modifiers = Modifiers::NONE;
+
+    if state.shift_key() {modifiers |= Modifiers::SHIFT}
+    if state.control_key() {modifiers |= Modifiers::CTRL}
+    if state.alt_key() {modifiers |= Modifiers::ALT}
+    if state.super_key() {modifiers |= Modifiers::SUPER}
+
+    modifiers
+}
+
+
+pub fn winit_touch_to_v39_touch(touch: &winit::event::Touch) -> Touch
+{
+    let phase = match touch.phase
+    {
+        winit::event::TouchPhase::Started => TouchPhase::Started,
+        winit::event::TouchPhase::Moved => TouchPhase::Moved,
+        winit::event::TouchPhase::Ended => TouchPhase::Ended,
+        winit::event::TouchPhase::Cancelled => TouchPhase::Cancelled,
+    };
+
+    Touch {
+        id: touch.id,
+        phase,
+        position: (touch.location.x, touch.location.y),
+        pressure: touch.force.map(|f| f.normalized() as f32),
     }
 }
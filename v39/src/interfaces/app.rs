@@ -1,19 +1,29 @@
 use once_cell::sync::OnceCell;
 
 use std::sync::{Mutex, Arc};
+use std::panic::AssertUnwindSafe;
+use std::collections::HashMap;
 //use std::sync::atomic::{Ordering, AtomicPtr};
 
 use crate::interfaces::event_handler::EventHandlerInterface;
 use crate::interfaces::input_manager::InputManagerInterface;
 use crate::interfaces::timer::TimerInterface;
 use crate::interfaces::renderer::RendererInterface;
+use crate::interfaces::window::WindowInterface;
+use crate::interfaces::profiler::ProfilerInterface;
+use crate::interfaces::tween::TweenInterface;
+use crate::interfaces::crash::CrashInterface;
+use crate::interfaces::logger::LoggerInterface;
+use crate::window::{WindowRequest, WindowMessage, WindowChange, WindowId, WindowSettings, Theme};
 use crate::input::InputManager;
 use crate::event::EngineEvent;
+use crate::quit::QuitState;
 use crate::prelude::*;
 
 use winit::{
-    event::{Event, WindowEvent, KeyEvent, ElementState},
-    event_loop::{ControlFlow, EventLoop},
+    event::{Event, WindowEvent, DeviceEvent, KeyEvent, ElementState},
+    event_loop::{ControlFlow, EventLoop, EventLoopBuilder},
+    error::EventLoopError,
     window::WindowBuilder,
     window::Window,
     keyboard::PhysicalKey,
@@ -29,11 +39,15 @@ pub struct App
     input_manager: InputManagerInterface,
     timer: TimerInterface,
     renderer: RendererInterface,
+    window: WindowInterface,
+    profiler: ProfilerInterface,
+    tweens: TweenInterface,
+    crash: CrashInterface,
+    logger: LoggerInterface,
 
-    window: Arc<Window>,
-    event_loop: Mutex<Option<EventLoop<()>>>,
+    event_loop: Mutex<Option<EventLoop<WindowMessage>>>,
 
-    quit: Mutex<bool>,
+    quit: Mutex<QuitState>,
 }
 
 
@@ -49,26 +63,33 @@ unsafe impl Send for App {}
 
 impl App
 {
-    pub(crate) fn init() -> V39Result<()>
+    pub(crate) fn init(config: EngineConfig) -> V39Result<()>
     {
+        // First, so the other interfaces are covered
+        let logger = LoggerInterface::new(config.log)?;
+        let crash = CrashInterface::new()?;
         let mut event_handler = EventHandlerInterface::new()?;
         let input_manager = InputManagerInterface::new()?;
         let timer = TimerInterface::new()?;
+        let profiler = ProfilerInterface::new()?;
+        let tweens = TweenInterface::new()?;
 
-        //TODO: Wrap the winit errors...
-        let event_loop = EventLoop::new().unwrap();
-        let window = WindowBuilder::new().build(&event_loop).unwrap();
+        let event_loop = EventLoopBuilder::with_user_event().build().context("Creating the event loop")?;
+        let window = WindowBuilder::new().build(&event_loop).context("Creating the main window")?;
         event_loop.set_control_flow(ControlFlow::Wait);
-        let event_loop = Mutex::new(Some(event_loop));
 
         let window = Arc::new(window);
-        let renderer = RendererInterface::new(window.clone())?;
+        let renderer = RendererInterface::new(window.clone()).context("Initializing the renderer")?;
+        let window = WindowInterface::new(window, event_loop.create_proxy())?;
+        let event_loop = Mutex::new(Some(event_loop));
 
-        event_handler.add_receiver(input_manager.clone());
+        event_handler.add_raw_receiver(input_manager.clone());
         event_handler.add_receiver(timer.clone());
+        event_handler.add_receiver(tweens.clone());
         event_handler.add_receiver(renderer.clone());
+        event_handler.add_receiver(window.clone());
 
-        let app = App {event_handler, input_manager, timer, window, event_loop, renderer, quit: Mutex::new(false)};
+        let app = App {event_handler, input_manager, timer, window, profiler, tweens, crash, logger, event_loop, renderer, quit: Mutex::new(QuitState::default())};
 
         if INSTANCE.set(app).is_err()
         {
@@ -107,65 +128,291 @@ impl App
         &self.renderer
     }
 
+    #[inline]
+    pub fn window(&self) -> &WindowInterface
+    {
+        &self.window
+    }
+
+    #[inline]
+    pub fn tweens(&self) -> &TweenInterface
+    {
+        &self.tweens
+    }
+
+    #[inline]
+    pub fn profiler(&self) -> &ProfilerInterface
+    {
+        &self.profiler
+    }
+
+    #[inline]
+    pub fn crash(&self) -> &CrashInterface
+    {
+        &self.crash
+    }
+
+    #[inline]
+    pub fn logger(&self) -> &LoggerInterface
+    {
+        &self.logger
+    }
+
+    /// Opens another window, it is created by the event loop shortly after the call returns.
+    /// `EventReceiver::window_opened` is called once it can be rendered to.
+    pub fn open_window(&self, settings: WindowSettings) -> WindowInterface
+    {
+        self.window.open(settings)
+    }
+
+    /// Handle to an open window, `None` once it was closed.
+    pub fn get_window(&self, id: WindowId) -> Option<WindowInterface>
+    {
+        self.window.get(id)
+    }
+
+    /// All open windows, including the main window.
+    pub fn windows(&self) -> Vec<WindowId>
+    {
+        self.window.ids()
+    }
+
+    /// Quits successfully, see `quit_with`.
     pub fn quit(&self)
     {
-        *self.quit.lock().unwrap() = true;
+        self.quit_with(QuitReason::success());
+    }
+
+    /// Asks to quit at the start of the next frame.
+    /// Receivers are asked first in `EventReceiver::quit_requested` and may veto,
+    /// otherwise they are told in `EventReceiver::quit` and the renderer is torn down.
+    /// `run` returns the reason as its `ExitStatus`.
+    pub fn quit_with(&self, reason: impl Into<QuitReason>)
+    {
+        let reason = reason.into();
+        info!("Quit requested with code {}", reason.code);
+
+        self.quit_state().request(reason);
+    }
+
+    /// Vetoes the pending quit, only has an effect from within `EventReceiver::quit_requested`.
+    pub fn cancel_quit(&self)
+    {
+        self.quit_state().veto();
     }
 
-    pub fn run(&self) -> V39Result<()>
+    fn quit_state(&self) -> std::sync::MutexGuard<'_, QuitState>
     {
-        // I deserve to be beaten...
+        self.quit.lock().unwrap()
+    }
+
+    /// Runs until the app quits and returns the reason it quit with.
+    /// Errors are reserved for failures of the event loop itself.
+    pub fn run(&self) -> V39Result<ExitStatus>
+    {
+        // The event loop can only be run once
         let event_loop = self.event_loop
-            .lock().unwrap()
-            .take().unwrap();
+            .lock().ok()
+            .and_then(|mut event_loop| event_loop.take())
+            .ok_or(V39Error::EventLoop(EventLoopError::AlreadyRunning))?;
 
-        std::thread::scope(|s| {
-            s.spawn(|| self.main_loop());
+        let result: V39Result<()> = std::thread::scope(|s| {
+            std::thread::Builder::new()
+                .name("v39 main loop".into())
+                .spawn_scoped(s, || self.guard_main_loop())?;
 
             let event_handler = self.event_handler();
 
-            event_loop.run(move |e, elwt| {
-                match e
+            let mut mods = input::Modifiers::NONE;
+
+            // winit has no minimize event, windows are minimized once they shrink to nothing
+            let mut minimized = HashMap::<WindowId, bool>::new();
+
+            let mut set_minimized = move |id: WindowId, now_minimized: bool| {
+                let minimized = minimized.entry(id).or_default();
+
+                if now_minimized != *minimized
+                {
+                    *minimized = now_minimized;
+
+                    if now_minimized {event_handler.queue_window_event(id, EngineEvent::WindowMinimize)}
+                    else {event_handler.queue_window_event(id, EngineEvent::WindowRestore)}
+                }
+            };
+
+            let apply_change = |change: WindowChange| {
+                match change
                 {
-                    Event::WindowEvent {event: WindowEvent::KeyboardInput {event, ..}, ..} => {
-                        if !event.repeat && let PhysicalKey::Code(key) = event.physical_key
+                    WindowChange::Opened(id, window) => {
+                        match self.renderer.add_window(id, window)
                         {
-                            let key = Some(input::translate::winit_key_to_v39_key(&key));
+                            Ok(()) => event_handler.queue_window_event(id, EngineEvent::WindowOpened(Some(id))),
 
-                            match event.state
-                            {
-                                ElementState::Pressed => event_handler.queue_engine_event(EngineEvent::KeyDown(key)),
-                                ElementState::Released => event_handler.queue_engine_event(EngineEvent::KeyUp(key)),
-                            }
+                            Err(e) => {
+                                error!("Window {id:?} can not be rendered to and is closed again: {}", e.report());
+                                if let Some(window) = self.window.get(id) {window.close()}
+                            },
                         }
                     },
 
-                    Event::WindowEvent {event, ..} => {
+                    WindowChange::Closed(id) => {
+                        self.renderer.remove_window(id);
+                        event_handler.queue_window_event(id, EngineEvent::WindowClosed(Some(id)));
+                    },
+                }
+            };
+
+            let run = || event_loop.run(move |e, elwt| {
+                match e
+                {
+                    Event::WindowEvent {window_id, event} => {
+                        // Events may still arrive for a window which was just closed
+                        let Some(id) = self.window.lookup(window_id) else {return};
+                        let queue = |event| event_handler.queue_window_event(id, event);
+
                         match event
                         {
-                            WindowEvent::CloseRequested => event_handler.queue_engine_event(EngineEvent::WindowClose),
-                            WindowEvent::Focused(true) => event_handler.queue_engine_event(EngineEvent::WindowFocus),
-                            WindowEvent::Focused(false) => event_handler.queue_engine_event(EngineEvent::WindowUnfocus),
-                            WindowEvent::Resized(size) => event_handler.queue_engine_event(EngineEvent::WindowResize(Some((size.width, size.height)))),
+                            WindowEvent::KeyboardInput {event, ..} => {
+                                let key = match event.physical_key
+                                {
+                                    PhysicalKey::Code(code) => input::translate::winit_key_to_v39_key(&code),
+                                    _ => None,
+                                };
+
+                                if let Some(key) = key
+                                {
+                                    match (event.state, event.repeat)
+                                    {
+                                        (ElementState::Pressed, false) => queue(EngineEvent::KeyDown(Some((key, mods)))),
+                                        (ElementState::Released, _) => queue(EngineEvent::KeyUp(Some((key, mods)))),
+
+                                        (ElementState::Pressed, true) => {
+                                            if self.input_manager.key_repeat_mode(key) == input::repeat::KeyRepeat::Os
+                                            {
+                                                queue(EngineEvent::KeyRepeat(Some((key, mods))));
+                                            }
+                                        },
+                                    }
+                                }
+                            },
+
+                            // Only the main window asks the app, other windows just close
+                            WindowEvent::CloseRequested if id == WindowId::MAIN => queue(EngineEvent::WindowClose),
+                            WindowEvent::CloseRequested => {
+                                if let Some(change) = self.window.apply(WindowMessage {id, request: WindowRequest::Close}, elwt)
+                                {
+                                    apply_change(change);
+                                }
+                            },
+
+                            WindowEvent::Focused(true) => queue(EngineEvent::WindowFocus),
+                            WindowEvent::Focused(false) => queue(EngineEvent::WindowUnfocus),
+                            WindowEvent::Resized(size) => {
+                                set_minimized(id, size.width == 0 || size.height == 0);
+
+                                if size.width != 0 && size.height != 0
+                                {
+                                    queue(EngineEvent::WindowResize(Some((size.width, size.height))));
+                                }
+                            },
+
+                            WindowEvent::Moved(position) => {
+                                self.window.update_monitor(id);
+                                queue(EngineEvent::WindowMoved(Some((position.x, position.y))));
+                            },
+
+                            WindowEvent::Occluded(occluded) => queue(EngineEvent::WindowOccluded(Some(occluded))),
+                            WindowEvent::ScaleFactorChanged {scale_factor, ..} => {
+                                self.window.update_monitor(id);
+                                queue(EngineEvent::ScaleFactorChanged(Some(scale_factor)));
+                            },
+
+                            WindowEvent::ThemeChanged(theme) => queue(EngineEvent::ThemeChanged(Some(Theme::from_winit(theme)))),
+                            WindowEvent::HoveredFile(path) => queue(EngineEvent::FileHovered(Some(path))),
+                            WindowEvent::HoveredFileCancelled => queue(EngineEvent::FileHoverCancelled),
+                            WindowEvent::DroppedFile(path) => queue(EngineEvent::FileDropped(Some(path))),
+                            WindowEvent::RedrawRequested => {
+                                // Nothing gets drawn to a hidden window
+                                if !self.renderer.is_window_paused(id)
+                                {
+                                    queue(EngineEvent::RedrawRequested);
+                                }
+                            },
+                            WindowEvent::ModifiersChanged(modifiers) => {
+                                mods = input::translate::winit_modifiers_to_v39_modifiers(&modifiers.state());
+                                queue(EngineEvent::ModifiersChanged(Some(mods)));
+                            },
+
+                            WindowEvent::CursorMoved {position, ..} => queue(EngineEvent::MouseMove(Some((position.x, position.y)))),
+
+                            WindowEvent::Touch(touch) => queue(EngineEvent::Touch(Some(input::translate::winit_touch_to_v39_touch(&touch)))),
+
+                            WindowEvent::MouseInput {state, button, ..} => {
+                                if let Some(button) = input::translate::winit_mouse_to_v39_mouse(&button)
+                                {
+                                    match state
+                                    {
+                                        ElementState::Pressed => queue(EngineEvent::MouseDown(Some(button))),
+                                        ElementState::Released => queue(EngineEvent::MouseUp(Some(button))),
+                                    }
+                                }
+                            },
+
                             _ => {},
                         }
                     },
 
+                    Event::DeviceEvent {event: DeviceEvent::MouseMotion {delta}, ..} => event_handler.queue_engine_event(EngineEvent::MouseMotion(Some(delta))),
+
+                    Event::UserEvent(message) => {
+                        if let WindowRequest::Minimized(minimize) = message.request
+                        {
+                            set_minimized(message.id, minimize);
+                        }
+
+                        if let Some(change) = self.window.apply(message, elwt)
+                        {
+                            apply_change(change);
+                        }
+                    },
+
                     _ => () 
                 }
 
-                if let Ok(quit) = self.quit.lock()
+                if self.quit_state().accepted().is_some()
                 {
-                    if *quit {elwt.exit();}
+                    elwt.exit();
                 }
             });
 
-            event_handler.fire_single_engine_event(EngineEvent::Quit(Some(0)));
+            let result = std::panic::catch_unwind(AssertUnwindSafe(run));
+
+            // The event loop may also end on its own or panic, the main loop has to follow it
+            match &result
+            {
+                Ok(Ok(())) => self.quit_state().accept(QuitReason::success()),
+                Ok(Err(_)) => self.quit_state().accept(QuitReason::with_code(1)),
+                Err(payload) => self.quit_state().accept(QuitReason::panicked(crate::crash::panic_message(&**payload))),
+            }
+
+            Ok(result.unwrap_or(Ok(()))?)
         });
 
         self.destroy();
+        result?;
 
-        Ok(())
+        let reason = self.quit_state().accepted().cloned().unwrap_or_default();
+        Ok(ExitStatus::new(reason))
+    }
+
+    /// A panic in the main loop quits the app, instead of leaving the event loop running without it.
+    fn guard_main_loop(&self)
+    {
+        if let Err(payload) = std::panic::catch_unwind(AssertUnwindSafe(|| self.main_loop()))
+        {
+            self.quit_state().accept(QuitReason::panicked(crate::crash::panic_message(&*payload)));
+            self.window.wake_event_loop();
+        }
     }
 
     fn main_loop(&self)
@@ -174,28 +421,91 @@ impl App
 
         event_handler.fire_single_engine_event(EngineEvent::Reset);
 
-        loop
+        let reason = loop
         {
-            if let Ok(quit) = self.quit.lock()
+            let request = self.quit_state().take_request();
+
+            if let Some(reason) = request
             {
-                if *quit {break}
+                event_handler.fire_single_engine_event(EngineEvent::QuitRequested(Some(reason.clone())));
+
+                if !self.quit_state().decide(reason)
+                {
+                    info!("Quit was vetoed");
+                }
+            }
+
+            if let Some(reason) = self.quit_state().accepted().cloned()
+            {
+                self.window.wake_event_loop();
+                break reason;
             }
 
-            event_handler.fire_engine_event(EngineEvent::KeyDown(None));
-            event_handler.fire_engine_event(EngineEvent::KeyUp(None));
+            self.profiler.begin_frame();
+
+            {
+                crate::profile_scope!("Input");
+                self.input_manager.begin_frame();
+                self.input_manager.flush_injected(self.timer.frame());
+                self.input_manager.queue_repeats();
+            }
+
+            event_handler.fire_input_events();
+            event_handler.fire_engine_event(EngineEvent::TouchGesture(None));
             event_handler.fire_single_engine_event(EngineEvent::FrameBegin);
-            event_handler.fire_single_engine_event(EngineEvent::Tick(Some(self.timer.delta_time().as_secs_f32())));
+            let delta = (self.timer.delta_time().as_secs_f32(), self.timer.game_delta_time().as_secs_f32());
+            event_handler.fire_single_engine_event(EngineEvent::Tick(Some(delta)));
+
+            let fixed_step = self.timer.fixed_step().as_secs_f32();
+
+            for _ in 0..self.timer.advance_fixed_step()
+            {
+                event_handler.fire_single_engine_event(EngineEvent::FixedTick(Some(fixed_step)));
+            }
+
+            event_handler.fire_engine_event(EngineEvent::TimerFired(None));
+            event_handler.fire_engine_event(EngineEvent::TweenFinished(None));
+
             event_handler.fire_engine_event(EngineEvent::WindowClose);
+            event_handler.fire_engine_event(EngineEvent::WindowFocus);
+            event_handler.fire_engine_event(EngineEvent::WindowUnfocus);
+            event_handler.fire_engine_event(EngineEvent::WindowResize(None));
+            event_handler.fire_engine_event(EngineEvent::WindowMoved(None));
+            event_handler.fire_engine_event(EngineEvent::WindowMinimize);
+            event_handler.fire_engine_event(EngineEvent::WindowRestore);
+            event_handler.fire_engine_event(EngineEvent::WindowOccluded(None));
+            event_handler.fire_engine_event(EngineEvent::ScaleFactorChanged(None));
+            event_handler.fire_engine_event(EngineEvent::ThemeChanged(None));
+            event_handler.fire_engine_event(EngineEvent::FileHovered(None));
+            event_handler.fire_engine_event(EngineEvent::FileHoverCancelled);
+            event_handler.fire_engine_event(EngineEvent::FileDropped(None));
+            event_handler.fire_engine_event(EngineEvent::RedrawRequested);
+            event_handler.fire_engine_event(EngineEvent::WindowOpened(None));
+            event_handler.fire_engine_event(EngineEvent::WindowClosed(None));
             event_handler.fire_events();
             event_handler.fire_single_engine_event(EngineEvent::FrameEnd);
 
-            self.timer.pad_frame_time();
-        }
+            {
+                crate::profile_scope!("Pad Frame");
+
+                match self.renderer.all_paused()
+                {
+                    true => self.timer.pad_hidden_frame(),
+                    false => self.timer.pad_frame_time(),
+                }
+            }
+        };
+
+        self.profiler.end_frame();
+
+        info!("Quitting with code {}", reason.code);
+        event_handler.fire_single_engine_event(EngineEvent::Quit(Some(reason)));
     }
 
     fn destroy(&self)
     {
         self.renderer.destroy();
         info!("App Destroyed");
+        self.logger.flush();
     }
 }
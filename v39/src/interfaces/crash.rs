@@ -0,0 +1,50 @@
+use crate::crash::CrashHandler;
+use crate::prelude::*;
+use std::path::{Path, PathBuf};
+
+
+/// Writes a crash report when any thread panics, with the panic message, backtrace,
+/// engine version, graphics device and the last log lines and engine events.
+/// A panic on the main loop thread quits the app and tears the renderer down,
+/// `App::run` then returns an `ExitStatus` with code 101.
+#[derive(Clone)]
+pub struct CrashInterface
+{
+    handle: &'static CrashHandler,
+}
+
+
+impl CrashInterface
+{
+    pub(crate) fn new() -> V39Result<Self>
+    {
+        let handle = CrashHandler::init()?;
+        info!("Crash Handler Initialized");
+
+        Ok(Self {handle})
+    }
+
+    /// Directory reports are written to, created if needed. "crash_reports" by default.
+    pub fn set_report_dir(&self, dir: impl AsRef<Path>)
+    {
+        self.handle.set_report_dir(dir.as_ref().to_path_buf());
+    }
+
+    pub fn report_dir(&self) -> PathBuf
+    {
+        self.handle.report_dir()
+    }
+
+    /// How many engine events are kept for reports, 100 by default.
+    /// The log lines are taken from `LoggerInterface::buffer`.
+    pub fn set_event_history_len(&self, events: usize)
+    {
+        self.handle.set_event_history_len(events);
+    }
+
+    /// Path of the last report written, e.g. to point the user to it after `App::run` returned.
+    pub fn last_report(&self) -> Option<PathBuf>
+    {
+        self.handle.last_report()
+    }
+}
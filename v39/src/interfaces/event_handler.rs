@@ -1,4 +1,8 @@
-use crate::event::{EventHandler, EngineEvent};
+use crate::event::{EventHandler, EngineEvent, Registered};
+use crate::input::context::InputLayer;
+use crate::window::WindowId;
+use crate::timer::Clock;
+use crate::crash::{CrashHandler, RecordedEvent};
 use crate::prelude::*;
 
 pub struct EventHandlerInterface
@@ -22,7 +26,14 @@ impl EventHandlerInterface
     {
         trace!("New EventReceiver registered");
         let receiver = Box::new(receiver);
-        self.handler.record_receiver(receiver);
+        self.handler.record_receiver(receiver, false);
+    }
+
+    /// Adds an engine receiver which sees all input, no matter which contexts are pushed.
+    pub(crate) fn add_raw_receiver<T>(&self, receiver: T)
+        where T: EventReceiver + Send + Sync + 'static
+    {
+        self.handler.record_receiver(Box::new(receiver), true);
     }
 
     pub fn queue_event(&self, event: Event)
@@ -33,37 +44,54 @@ impl EventHandlerInterface
 
     pub(crate) fn queue_engine_event(&self, event: EngineEvent)
     {
-        trace!("EngineEvent queued: {event:?}");
-        self.handler.record_engine_event(event);
+        self.queue_window_event(WindowId::MAIN, event);
+    }
+
+    pub(crate) fn queue_window_event(&self, window: WindowId, event: EngineEvent)
+    {
+        trace!("EngineEvent queued for {window:?}: {event:?}");
+        self.handler.record_engine_event(event, window);
+    }
+
+    /// The window the engine event which is currently dispatched originates from.
+    /// Events which do not belong to a window, like ticks or pad input, report the main window.
+    pub fn event_window(&self) -> WindowId
+    {
+        self.handler.current_window()
     }
 
     pub(crate) fn fire_engine_event(&self, event_kind: EngineEvent) -> V39Result<()>
     {
+        crate::profile_scope!(event_kind.kind_name());
         trace!("Begin dispathing {event_kind:?} engine events...");
-        self.event_dispatch_begin();
 
-        self.handler.snapchot_receiver_queue();
-        self.handler.snapshot_engine_event_queue(|e| e.var_eq(&event_kind));
-        
-        let events = self.handler.fetch_engine_event_snapshots();
- 
-        for event in events
-        {
-            self.handler.foreach_receiver_snapshot(|rec|{
-                self.match_event(event.clone(), rec)
-            });
-        }
+        self.handler.dispatch_engine_events(|e| e.var_eq(&event_kind), |event, rec| {
+            self.match_event(event.clone(), rec)
+        });
 
-        self.handler.apply_receiver_snapshot();
-        self.event_dispatch_end();
         trace!("Finished dispathing {event_kind:?} engine events");
         Ok(())
     }
 
+    /// Dispatches the key, mouse, pad and touch events of the frame in the order they arrived,
+    /// so e.g. a key released and pressed again within one frame ends the frame held.
+    pub(crate) fn fire_input_events(&self) -> V39Result<()>
+    {
+        crate::profile_scope!("Input Events");
+        trace!("Begin dispathing input events...");
+
+        self.handler.dispatch_engine_events(EngineEvent::is_input, |event, rec| {
+            self.match_event(event.clone(), rec)
+        });
+
+        trace!("Finished dispathing input events");
+        Ok(())
+    }
+
     pub(crate) fn fire_events(&self) -> V39Result<()>
     {
+        crate::profile_scope!("Events");
         trace!("Begin dispatching events...");
-        self.event_dispatch_begin();
         
         self.handler.snapchot_receiver_queue();
         self.handler.snapchot_event_queue();
@@ -72,20 +100,24 @@ impl EventHandlerInterface
 
         while let Some(e) = events.pop()
         {
-            self.handler.foreach_receiver_snapshot(|rec| rec.dispatch_event(e.to_owned()));
+            if let Some(crash) = CrashHandler::get()
+            {
+                crash.record_event(RecordedEvent::User(e.clone()));
+            }
+
+            self.handler.foreach_receiver_snapshot(|rec| rec.receiver.dispatch_event(e.to_owned()));
         }
 
         self.handler.apply_receiver_snapshot();
-        
-        self.event_dispatch_end();
         trace!("Finished dispatching events");
         Ok(())
     }
 
     pub(crate) fn fire_single_engine_event(&self, event: EngineEvent) -> V39Result<()>
     {
+        crate::profile_scope!(event.kind_name());
         trace!("Begin Single EngineEvent Dispatch of {event:?}");
-        self.event_dispatch_begin();
+        crate::event::remember(&event, WindowId::MAIN);
         self.handler.snapchot_receiver_queue();
 
         self.handler.foreach_receiver_snapshot(|rec| {
@@ -93,39 +125,98 @@ impl EventHandlerInterface
         });
 
         self.handler.apply_receiver_snapshot();
-        self.event_dispatch_end();
         trace!("End Single Event Dispatch of {event:?}");
 
         Ok(())
     }
 
-    pub(crate) fn event_dispatch_begin(&self)
+    fn match_event(&self,  event: EngineEvent, registered: &mut Registered) -> V39Result<()>
     {
-        get_v39().input_manager().event_begin();
-    }
+        let input_manager = get_v39().input_manager();
+        let rec = &mut registered.receiver;
 
-    pub(crate) fn event_dispatch_end(&self)
-    {
-        get_v39().input_manager().event_end();
-    }
+        let context = match registered.raw
+        {
+            true => InputLayer::Raw,
+            false => InputLayer::from(rec.input_context()),
+        };
+
+        let visible = match event
+        {
+            EngineEvent::KeyDown(Some((key, _))) | EngineEvent::KeyRepeat(Some((key, _))) => input_manager.key_visible(key, context),
+
+            // Key releases are always delivered, otherwise a key held while
+            // a context gets pushed would never be released for the receiver.
+            // Keys taken by an input capture never reached the receiver in the first place.
+            // The same goes for buttons and fingers lifted while `consume_all` hides them.
+            EngineEvent::KeyUp(Some((key, _))) => !input_manager.key_captured(key, context),
+            EngineEvent::MouseUp(Some(button)) => !input_manager.mouse_captured(button, context),
+            EngineEvent::PadUp(Some(button)) => !input_manager.pad_captured(button, context),
+
+            EngineEvent::Touch(Some(touch)) => {
+                matches!(touch.phase, input::TouchPhase::Ended | input::TouchPhase::Cancelled) || input_manager.input_visible(context)
+            },
+
+            EngineEvent::MouseDown(Some(button)) => input_manager.mouse_visible(button, context),
+            EngineEvent::PadDown(Some(button)) => input_manager.pad_visible(button, context),
+
+            EngineEvent::MouseMove(_) | EngineEvent::MouseMotion(_) | EngineEvent::PadAxis(_) | EngineEvent::TouchGesture(_) => {
+                input_manager.input_visible(context)
+            },
+
+            _ => true,
+        };
+
+        if !visible {return Ok(())}
 
-    fn match_event(&self,  event: EngineEvent, rec: &mut Box<dyn EventReceiver + Send + Sync>) -> V39Result<()>
-    {
         match event
         {
             EngineEvent::Reset => rec.reset(),
-            EngineEvent::KeyUp(Some(key)) => rec.key_up(key),
-            EngineEvent::KeyDown(Some(key)) => rec.key_down(key),
+            EngineEvent::KeyUp(Some((key, mods))) => rec.key_up(key, mods),
+            EngineEvent::KeyDown(Some((key, mods))) => rec.key_down(key, mods),
+            EngineEvent::KeyRepeat(Some((key, mods))) => rec.key_repeat(key, mods),
+            EngineEvent::ModifiersChanged(Some(mods)) => rec.modifiers_changed(mods),
+            EngineEvent::MouseDown(Some(button)) => rec.mouse_down(button),
+            EngineEvent::MouseUp(Some(button)) => rec.mouse_up(button),
+            EngineEvent::MouseMove(Some(position)) => rec.mouse_move(position),
+            EngineEvent::MouseMotion(Some(delta)) => rec.mouse_motion(delta),
+            EngineEvent::PadDown(Some(button)) => rec.pad_down(button),
+            EngineEvent::PadUp(Some(button)) => rec.pad_up(button),
+            EngineEvent::PadAxis(Some((axis, value))) => rec.pad_axis(axis, value),
+            EngineEvent::Touch(Some(touch)) => rec.touch(touch),
+            EngineEvent::TouchGesture(Some(gesture)) => rec.touch_gesture(gesture),
             EngineEvent::FrameBegin => rec.frame_begin(),
             EngineEvent::FrameEnd => rec.frame_end(),
-            EngineEvent::Tick(Some(delta)) => rec.tick(delta),
-            EngineEvent::Quit(Some(reason)) => rec.quit(reason),
+            EngineEvent::Tick(Some((real, game))) => match rec.clock()
+            {
+                Clock::Real => rec.tick(real),
+                Clock::Game => rec.tick(game),
+            },
+
+            EngineEvent::FixedTick(Some(delta)) => rec.fixed_tick(delta),
+            EngineEvent::TimerFired(Some(id)) => rec.timer_fired(id),
+            EngineEvent::TweenFinished(Some(id)) => rec.tween_finished(id),
+            EngineEvent::QuitRequested(Some(reason)) => rec.quit_requested(&reason),
+            EngineEvent::Quit(Some(reason)) => rec.quit(&reason),
             EngineEvent::WindowClose => rec.window_close(),
+            EngineEvent::WindowOpened(Some(window)) => rec.window_opened(window),
+            EngineEvent::WindowClosed(Some(window)) => rec.window_closed(window),
             EngineEvent::WindowResize(Some(size)) => rec.window_resize(size),
             EngineEvent::WindowFocus => rec.window_focus(),
             EngineEvent::WindowUnfocus => rec.window_unfocus(),
+            EngineEvent::WindowMinimize => rec.window_minimize(),
+            EngineEvent::WindowRestore => rec.window_restore(),
+            EngineEvent::WindowMoved(Some(position)) => rec.window_moved(position),
+            EngineEvent::WindowOccluded(Some(occluded)) => rec.window_occluded(occluded),
+            EngineEvent::ScaleFactorChanged(Some(scale)) => rec.scale_factor_changed(scale),
+            EngineEvent::ThemeChanged(Some(theme)) => rec.theme_changed(theme),
+            EngineEvent::FileHovered(Some(path)) => rec.file_hovered(path),
+            EngineEvent::FileHoverCancelled => rec.file_hover_cancelled(),
+            EngineEvent::FileDropped(Some(path)) => rec.file_dropped(path),
+            EngineEvent::RedrawRequested => rec.redraw_requested(),
             
             _ => Ok(()),
         }
     }
 }
+
@@ -1,5 +1,12 @@
 use crate::prelude::*;
 use crate::input::InputManager;
+use crate::event::EngineEvent;
+use crate::input::axis::{Axis1D, Axis2D};
+use crate::input::gesture::Gesture;
+use crate::input::context::{InputContext, InputLayer};
+use crate::input::repeat::KeyRepeat;
+use crate::input::capture::InputCapture;
+use std::time::Duration;
 
 
 #[derive(Clone)]
@@ -19,54 +26,459 @@ impl InputManagerInterface
         Ok(Self {handler})
     }
 
+    /// Whether the key was pressed this frame, as seen by the base layer below all input contexts.
     pub fn is_down(&self, key: input::V39Key) -> bool
     {
-        self.handler.down_snapshot_contains(key)
+        self.handler.keys().is_down(key) && self.handler.key_visible(key, InputLayer::Base)
     }
 
+    /// Whether the key was released this frame, as seen by the base layer below all input contexts.
+    pub fn is_up(&self, key: input::V39Key) -> bool
+    {
+        self.handler.keys().is_up(key) && self.handler.key_visible(key, InputLayer::Base)
+    }
+
+    /// Whether the key is held down, including the frame it was pressed in,
+    /// as seen by the base layer below all input contexts.
     pub fn is_held(&self, key: input::V39Key) -> bool
     {
-        self.handler.held_snapshot_contains(key)
+        self.handler.keys().is_held(key) && self.handler.key_visible(key, InputLayer::Base)
+    }
+
+    /// Engine time at which the currently held key was pressed, see `TimerInterface::elapsed`.
+    pub fn pressed_at(&self, key: input::V39Key) -> Option<Duration>
+    {
+        self.handler.keys().pressed_at(key)
+    }
+
+    /// For how long the key has been held down, `None` if it is not held.
+    pub fn held_duration(&self, key: input::V39Key) -> Option<Duration>
+    {
+        self.handler.keys().held_duration(key, get_v39().timer().elapsed())
+    }
+
+    /// Currently pressed modifiers.
+    pub fn modifiers(&self) -> input::Modifiers
+    {
+        self.handler.modifiers()
+    }
+
+    /// Whether the shortcut was pressed this frame, modifiers have to match exactly.
+    pub fn is_shortcut_down(&self, shortcut: &input::Shortcut) -> bool
+    {
+        self.is_down(shortcut.key) && self.modifiers() == shortcut.modifiers
+    }
+
+    /// Sets the key repeat used when no input context configures one, the default is `KeyRepeat::Off`.
+    pub fn set_key_repeat(&self, repeat: KeyRepeat)
+    {
+        self.handler.set_repeat(repeat);
+    }
+
+    /// The key repeat which currently applies to the key.
+    pub fn key_repeat_mode(&self, key: input::V39Key) -> KeyRepeat
+    {
+        self.handler.repeat_mode(key)
+    }
+
+    /// Queues the engine side key repeats which are due.
+    pub(crate) fn queue_repeats(&self)
+    {
+        let now = get_v39().timer().elapsed();
+        let due = self.handler.repeats().due(now, |key| self.handler.repeat_mode(key));
+
+        let mods = self.handler.modifiers();
+
+        for key in due
+        {
+            get_v39().event_handler().queue_engine_event(EngineEvent::KeyRepeat(Some((key, mods))));
+        }
+    }
+
+    /// Pushes an input context on top of the stack.
+    /// A context with the same name is removed from the stack first.
+    pub fn push_context(&self, context: InputContext)
+    {
+        trace!("Input context {} pushed", context.name);
+        self.handler.push_context(context);
+    }
+
+    pub fn pop_context(&self) -> Option<InputContext>
+    {
+        self.handler.pop_context()
+    }
+
+    pub fn remove_context(&self, name: &str) -> Option<InputContext>
+    {
+        self.handler.remove_context(name)
+    }
+
+    pub fn top_context(&self) -> Option<InputContext>
+    {
+        self.handler.top_context()
+    }
+
+    pub fn is_context_active(&self, name: &str) -> bool
+    {
+        self.handler.context_active(name)
+    }
+
+    /// Queries the input state as seen from the named context.
+    pub fn context<'a>(&'a self, name: &'a str) -> ContextInput<'a>
+    {
+        ContextInput {handler: self.handler, name}
+    }
+
+    /// Whether any key bound to the action was pressed this frame.
+    /// The action is resolved by the top most context that binds it.
+    pub fn is_action_down(&self, action: &str) -> bool
+    {
+        match self.handler.resolve_action(action)
+        {
+            Some((name, keys)) => keys.into_iter().any(|k| self.context(&name).is_down(k)),
+            None => false,
+        }
+    }
+
+    /// Whether any key bound to the action is held down.
+    /// The action is resolved by the top most context that binds it.
+    pub fn is_action_held(&self, action: &str) -> bool
+    {
+        match self.handler.resolve_action(action)
+        {
+            Some((name, keys)) => keys.into_iter().any(|k| self.context(&name).is_held(k)),
+            None => false,
+        }
     }
 
-    pub(crate) fn event_begin(&self)
+    pub(crate) fn key_visible(&self, key: input::V39Key, layer: InputLayer) -> bool
     {
-        self.handler.snapshot_keys_down();
-        self.handler.snapshot_keys_up();
-        self.handler.snapshot_keys_held();
+        self.handler.key_visible(key, layer)
     }
 
-    pub(crate) fn event_end(&self)
+    pub(crate) fn key_captured(&self, key: input::V39Key, layer: InputLayer) -> bool
     {
-        self.handler.apply_down_snapshot();
-        self.handler.apply_up_snapshot();
-        self.handler.apply_held_snapshot();
+        self.handler.key_captured(key, layer)
+    }
+
+    pub(crate) fn input_visible(&self, layer: InputLayer) -> bool
+    {
+        self.handler.input_visible(layer)
+    }
+
+    pub(crate) fn mouse_visible(&self, button: input::V39Mouse, layer: InputLayer) -> bool
+    {
+        self.handler.mouse_visible(button, layer)
+    }
+
+    pub(crate) fn mouse_captured(&self, button: input::V39Mouse, layer: InputLayer) -> bool
+    {
+        self.handler.mouse_captured(button, layer)
+    }
+
+    pub(crate) fn pad_visible(&self, button: input::V39Pad, layer: InputLayer) -> bool
+    {
+        self.handler.pad_visible(button, layer)
+    }
+
+    pub(crate) fn pad_captured(&self, button: input::V39Pad, layer: InputLayer) -> bool
+    {
+        self.handler.pad_captured(button, layer)
+    }
+
+    /// Listens for the next key, mouse button or pad button, e.g. to rebind a control.
+    /// Esc cancels the capture, a lone modifier is captured once it is released.
+    /// The captured input is hidden from receivers and queries until it is released.
+    /// Starting a capture cancels the one that is still pending.
+    pub fn capture_next_input(&self) -> InputCapture
+    {
+        trace!("Input capture started");
+        self.handler.capture().start()
+    }
+
+    pub fn is_mouse_down(&self, button: input::V39Mouse) -> bool
+    {
+        self.handler.mouse().is_down(button) && self.handler.mouse_visible(button, InputLayer::Base)
+    }
+
+    pub fn is_mouse_up(&self, button: input::V39Mouse) -> bool
+    {
+        self.handler.mouse().is_up(button) && self.handler.mouse_visible(button, InputLayer::Base)
+    }
+
+    pub fn is_mouse_held(&self, button: input::V39Mouse) -> bool
+    {
+        self.handler.mouse().is_held(button) && self.handler.mouse_visible(button, InputLayer::Base)
+    }
+
+    /// Cursor position in physical pixels relative to the top left corner of the window,
+    /// see `mouse_window` for the window the position belongs to.
+    pub fn mouse_position(&self) -> (f64, f64)
+    {
+        self.handler.mouse_position()
+    }
+
+    /// The window the cursor last moved in.
+    pub fn mouse_window(&self) -> crate::window::WindowId
+    {
+        self.handler.mouse_window()
+    }
+
+    /// Raw mouse movement accumulated over the current frame.
+    /// Unlike the cursor position it keeps changing while the cursor is locked.
+    pub fn mouse_delta(&self) -> (f64, f64)
+    {
+        self.handler.mouse_delta()
+    }
+
+    /// Every finger currently on the screen, ordered by id.
+    pub fn touches(&self) -> Vec<input::Touch>
+    {
+        self.handler.touches()
+    }
+
+    pub fn touch(&self, id: u64) -> Option<input::Touch>
+    {
+        self.handler.touch(id)
+    }
+
+    pub fn is_pad_down(&self, button: input::V39Pad) -> bool
+    {
+        self.handler.pad().is_down(button) && self.handler.pad_visible(button, InputLayer::Base)
+    }
+
+    pub fn is_pad_up(&self, button: input::V39Pad) -> bool
+    {
+        self.handler.pad().is_up(button) && self.handler.pad_visible(button, InputLayer::Base)
+    }
+
+    pub fn is_pad_held(&self, button: input::V39Pad) -> bool
+    {
+        self.handler.pad().is_held(button) && self.handler.pad_visible(button, InputLayer::Base)
+    }
+
+    /// Injects a synthetic input which is delivered at the start of the given frame,
+    /// see `TimerInterface::frame`. Inputs for frames that already passed are delivered on the next frame.
+    /// Injected inputs are indistinguishable from real ones for receivers.
+    pub fn inject(&self, frame: u64, event: input::InputEvent)
+    {
+        trace!("Input injected for frame {frame}: {event:?}");
+        self.handler.inject(frame, event);
+    }
+
+    /// Injects a synthetic input which is delivered on the next frame.
+    pub fn inject_now(&self, event: input::InputEvent)
+    {
+        self.inject(get_v39().timer().frame(), event);
+    }
+
+    /// Number of injected inputs which have not been delivered yet.
+    pub fn pending_injections(&self) -> usize
+    {
+        self.handler.pending_injections()
+    }
+
+    /// Moves all injected inputs which are due into the engine event queue.
+    pub(crate) fn flush_injected(&self, frame: u64)
+    {
+        let event_handler = get_v39().event_handler();
+
+        // Modifiers are tracked through the batch, so an injected Ctrl
+        // applies to the keys injected after it within the same frame
+        let mut mods = self.handler.modifiers();
+
+        for event in self.handler.take_injected(frame)
+        {
+            match event
+            {
+                input::InputEvent::KeyDown(key) => mods |= input::Modifiers::from_key(key).unwrap_or_default(),
+                input::InputEvent::KeyUp(key) => mods.remove(input::Modifiers::from_key(key).unwrap_or_default()),
+                _ => {},
+            }
+
+            event_handler.queue_engine_event(event.into_engine_event(mods));
+        }
+    }
+
+    /// The unprocessed value of a pad axis, without deadzones or curves applied.
+    pub fn axis_raw(&self, axis: input::V39Axis) -> (f32, f32)
+    {
+        self.handler.axis(axis)
+    }
+
+    pub fn axis(&self, axis: &Axis1D) -> f32
+    {
+        axis.value(self)
+    }
+
+    pub fn axis_2d(&self, axis: &Axis2D) -> (f32, f32)
+    {
+        axis.value(self)
+    }
+
+    /// Registers a gesture, once it is recognized an `Event` with the given id is queued.
+    pub fn add_gesture(&self, id: impl Into<u32>, gesture: Gesture)
+    {
+        self.handler.add_gesture(id.into(), gesture);
+    }
+
+    pub fn remove_gesture(&self, id: impl Into<u32>)
+    {
+        self.handler.remove_gesture(id.into());
+    }
+
+    fn queue_gestures(&self, recognized: Vec<u32>)
+    {
+        for id in recognized
+        {
+            trace!("Gesture {id} recognized");
+            get_v39().event_handler().queue_event(Event::new(id, vec![]));
+        }
+    }
+
+    /// Starts a new input frame, must be called before the input events of the frame are dispatched.
+    pub(crate) fn begin_frame(&self)
+    {
+        self.handler.begin_frame();
     }
 }
 
+/// Input state as seen by a single input context.
+pub struct ContextInput<'a>
+{
+    handler: &'static InputManager,
+    name: &'a str,
+}
+
+
+impl<'a> ContextInput<'a>
+{
+    pub fn is_down(&self, key: input::V39Key) -> bool
+    {
+        self.handler.keys().is_down(key) && self.handler.key_visible(key, InputLayer::Context(self.name))
+    }
+
+    pub fn is_up(&self, key: input::V39Key) -> bool
+    {
+        self.handler.keys().is_up(key) && self.handler.key_visible(key, InputLayer::Context(self.name))
+    }
+
+    pub fn is_held(&self, key: input::V39Key) -> bool
+    {
+        self.handler.keys().is_held(key) && self.handler.key_visible(key, InputLayer::Context(self.name))
+    }
+}
+
+
 impl EventReceiver for InputManagerInterface
 {
     fn frame_begin(&mut self) -> V39Result<()>
     {
-        self.handler.down_held_up_conversion();
+        let recognized = self.handler.update_gestures(get_v39().timer().elapsed());
+        self.queue_gestures(recognized);
         Ok(())
     }
 
-    fn frame_end(&mut self) -> V39Result<()> 
+    fn window_unfocus(&mut self) -> V39Result<()>
     {
-        self.handler.clear_down_snapshot();
-        Ok(())    
+        // Releases would be delivered to the unfocused window, so nothing may stay held.
+        // Receivers get the releases with the input of the next frame.
+        let event_handler = get_v39().event_handler();
+
+        for event in self.handler.release_all()
+        {
+            event_handler.queue_engine_event(event.into_engine_event(input::Modifiers::NONE));
+        }
+
+        Ok(())
+    }
+
+    fn modifiers_changed(&mut self, mods: input::Modifiers) -> V39Result<()>
+    {
+        self.handler.set_modifiers(mods);
+        Ok(())
+    }
+
+    fn key_up(&mut self, key: input::V39Key, mods: input::Modifiers) -> V39Result<()> 
+    {
+        self.handler.keys().release(key);
+        self.handler.repeats().release(key);
+        self.handler.gesture_key_up(key);
+        self.handler.capture().key_up(key);
+        Ok(())
+    }
+
+    fn key_down(&mut self, key: input::V39Key, mods: input::Modifiers) -> V39Result<()> 
+    {
+        let now = get_v39().timer().elapsed();
+        self.handler.keys().press(key, now);
+        self.handler.repeats().press(key, now);
+        self.handler.capture().key_down(key, mods);
+
+        // Gestures sit below every context, keys consumed by one do not reach them
+        if !self.handler.key_visible(key, InputLayer::Base) {return Ok(())}
+
+        let recognized = self.handler.gesture_key_down(key, now);
+        self.queue_gestures(recognized);
+        Ok(())
+    }
+
+    fn mouse_down(&mut self, button: input::V39Mouse) -> V39Result<()>
+    {
+        self.handler.mouse().press(button, get_v39().timer().elapsed());
+        self.handler.capture().mouse_down(button);
+        Ok(())
+    }
+
+    fn mouse_up(&mut self, button: input::V39Mouse) -> V39Result<()>
+    {
+        self.handler.mouse().release(button);
+        self.handler.capture().mouse_up(button);
+        Ok(())
+    }
+
+    fn mouse_move(&mut self, position: (f64, f64)) -> V39Result<()>
+    {
+        self.handler.set_mouse_position(position, get_v39().event_handler().event_window());
+        Ok(())
+    }
+
+    fn mouse_motion(&mut self, delta: (f64, f64)) -> V39Result<()>
+    {
+        self.handler.add_mouse_delta(delta);
+        Ok(())
+    }
+
+    fn touch(&mut self, touch: input::Touch) -> V39Result<()>
+    {
+        let gestures = self.handler.update_touch(touch, get_v39().timer().elapsed());
+
+        // Queued while touches are dispatched, so they are delivered within the same frame
+        for gesture in gestures
+        {
+            get_v39().event_handler().queue_engine_event(EngineEvent::TouchGesture(Some(gesture)));
+        }
+
+        Ok(())
+    }
+
+    fn pad_down(&mut self, button: input::V39Pad) -> V39Result<()>
+    {
+        self.handler.pad().press(button, get_v39().timer().elapsed());
+        self.handler.capture().pad_down(button);
+        Ok(())
     }
 
-    fn key_up(&mut self, key: input::V39Key) -> V39Result<()> 
+    fn pad_up(&mut self, button: input::V39Pad) -> V39Result<()>
     {
-        self.handler.push_key_up(key);
+        self.handler.pad().release(button);
+        self.handler.capture().pad_up(button);
         Ok(())
     }
 
-    fn key_down(&mut self, key: input::V39Key) -> V39Result<()> 
+    fn pad_axis(&mut self, axis: input::V39Axis, value: (f32, f32)) -> V39Result<()>
     {
-        self.handler.push_key_down(key);
+        self.handler.set_axis(axis, value);
         Ok(())
     }
 }
@@ -0,0 +1,67 @@
+use crate::logging::{Logger, LogBuffer, LogFilters, LogSink, SinkId};
+use crate::prelude::*;
+use log::LevelFilter;
+
+
+/// Hands log entries to any number of sinks, like the console, rotating files and
+/// in-memory buffers. Set up through `LogConfig` in the engine config passed to `v39::init_with`.
+#[derive(Clone)]
+pub struct LoggerInterface
+{
+    handle: &'static Logger,
+}
+
+
+impl LoggerInterface
+{
+    pub(crate) fn new(config: LogConfig) -> V39Result<Self>
+    {
+        let handle = Logger::init(config)?;
+        info!("Logger Initialized");
+
+        Ok(Self {handle})
+    }
+
+    pub fn add_sink(&self, sink: impl LogSink + 'static) -> SinkId
+    {
+        self.handle.add_sink(Box::new(sink))
+    }
+
+    /// Flushes and drops the sink.
+    pub fn remove_sink(&self, id: SinkId)
+    {
+        self.handle.remove_sink(id);
+    }
+
+    /// The engine's buffer of the last log lines, which crash reports include.
+    /// Its size is set with `LogConfig::buffer_capacity`.
+    pub fn buffer(&self) -> LogBuffer
+    {
+        self.handle.buffer().clone()
+    }
+
+    pub fn filters(&self) -> LogFilters
+    {
+        self.handle.filters()
+    }
+
+    pub fn set_filters(&self, filters: LogFilters)
+    {
+        self.handle.update_filters(|current| *current = filters);
+    }
+
+    pub fn set_level(&self, level: LevelFilter)
+    {
+        self.handle.update_filters(|filters| filters.set_default(level));
+    }
+
+    pub fn set_module_level(&self, module: impl Into<String>, level: LevelFilter)
+    {
+        self.handle.update_filters(|filters| filters.set_module(module, level));
+    }
+
+    pub fn flush(&self)
+    {
+        log::logger().flush();
+    }
+}
@@ -2,4 +2,9 @@ pub mod app;
 pub mod event_handler;
 pub mod input_manager;
 pub mod timer;
+pub mod window;
 pub mod renderer;
+pub mod profiler;
+pub mod tween;
+pub mod crash;
+pub mod logger;
@@ -0,0 +1,77 @@
+use crate::profiler::{Profiler, ProfiledFrame};
+use crate::prelude::*;
+
+
+/// CPU profiler for the main loop.
+/// While enabled, every engine phase, every receiver call and every `profile_scope!`
+/// is recorded into a ring buffer of the last frames.
+#[derive(Clone)]
+pub struct ProfilerInterface
+{
+    handle: &'static Profiler,
+}
+
+
+impl ProfilerInterface
+{
+    pub(crate) fn new() -> V39Result<Self>
+    {
+        let handle = Profiler::init()?;
+        info!("Profiler Initialized");
+
+        Ok(Self {handle})
+    }
+
+    /// Starts recording with the next frame, the profiler is disabled by default.
+    pub fn set_enabled(&self, enabled: bool)
+    {
+        self.handle.set_enabled(enabled);
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool
+    {
+        self.handle.is_enabled()
+    }
+
+    /// Number of frames kept, 300 by default.
+    pub fn set_capacity(&self, frames: usize)
+    {
+        self.handle.ring().set_capacity(frames);
+    }
+
+    /// The recorded frames, oldest first.
+    pub fn frames(&self) -> Vec<ProfiledFrame>
+    {
+        self.handle.ring().frames()
+    }
+
+    pub fn clear(&self)
+    {
+        self.handle.ring().clear();
+    }
+
+    /// Writes the recorded frames as Chrome Trace Event JSON.
+    pub fn write_chrome_trace(&self, mut out: impl std::io::Write) -> V39Result<()>
+    {
+        self.handle.ring().write_chrome_trace(&mut out)?;
+        Ok(())
+    }
+
+    /// Writes the recorded frames to a JSON file for Perfetto or chrome://tracing, replacing it if it exists.
+    pub fn dump_chrome_trace(&self, path: impl AsRef<std::path::Path>) -> V39Result<()>
+    {
+        let file = std::fs::File::create(path)?;
+        self.write_chrome_trace(std::io::BufWriter::new(file))
+    }
+
+    pub(crate) fn begin_frame(&self)
+    {
+        self.handle.begin_frame();
+    }
+
+    pub(crate) fn end_frame(&self)
+    {
+        self.handle.end_frame();
+    }
+}
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use crate::renderer::Renderer;
+use crate::window::WindowId;
 use std::sync::Arc;
 
 
@@ -21,11 +22,55 @@ impl RendererInterface
     {
         self.handle.destroy();
     }
+
+    pub(crate) fn add_window(&self, id: WindowId, window: Arc<winit::window::Window>) -> V39Result<()>
+    {
+        self.handle.add_window(id, window)
+    }
+
+    pub(crate) fn remove_window(&self, id: WindowId)
+    {
+        self.handle.remove_window(id);
+    }
+
+    /// Whether rendering is paused because the main window is minimized or occluded.
+    pub fn is_paused(&self) -> bool
+    {
+        self.handle.is_paused(WindowId::MAIN)
+    }
+
+    /// Whether rendering to the window is paused because it is minimized or occluded.
+    pub fn is_window_paused(&self, id: WindowId) -> bool
+    {
+        self.handle.is_paused(id)
+    }
+
+    /// Whether no window is visible, the main loop then slows down.
+    pub fn all_paused(&self) -> bool
+    {
+        self.handle.all_paused()
+    }
 }
 
 
 impl EventReceiver for RendererInterface
 {
+    fn window_minimize(&mut self) -> V39Result<()>
+    {
+        self.handle.set_minimized(get_v39().event_handler().event_window(), true);
+        Ok(())
+    }
 
+    fn window_restore(&mut self) -> V39Result<()>
+    {
+        self.handle.set_minimized(get_v39().event_handler().event_window(), false);
+        Ok(())
+    }
+
+    fn window_occluded(&mut self, occluded: bool) -> V39Result<()>
+    {
+        self.handle.set_occluded(get_v39().event_handler().event_window(), occluded);
+        Ok(())
+    }
 }
 
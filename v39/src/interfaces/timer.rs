@@ -1,8 +1,18 @@
-use crate::timer::Timer;
+use crate::timer::{Timer, Clock};
+use crate::timer::pacing::{FramePacing, PacingStats};
+use crate::timer::schedule::{Schedule, TimerId, TimerStatus};
+use crate::timer::stats::FrameStats;
+use crate::timer::source::TimeSource;
+use std::sync::Arc;
+use crate::event::EngineEvent;
 use crate::prelude::*;
 use std::time::Duration;
 
 
+/// Frame time of the main loop while every window is minimized or occluded.
+const HIDDEN_FRAME_TIME: Duration = Duration::from_millis(100);
+
+
 #[derive(Clone)]
 pub struct TimerInterface
 {
@@ -18,38 +28,294 @@ impl TimerInterface
         Ok(Self {handle})
     }
 
+    /// Real duration of the last frame.
     #[inline]
     pub fn delta_time(&self) -> Duration
     {
         self.handle.delta_time()
     }
 
-    pub(crate) fn pad_frame_time(&self)
+    /// Duration of the last frame in game time, zero while paused.
+    #[inline]
+    pub fn game_delta_time(&self) -> Duration
     {
-        if let Some(target) = self.handle.frame_time()
+        self.handle.game_delta_time()
+    }
+
+    pub fn delta_time_of(&self, clock: Clock) -> Duration
+    {
+        match clock
         {
-            let delta = self.handle.current_frame_time();
+            Clock::Real => self.delta_time(),
+            Clock::Game => self.game_delta_time(),
+        }
+    }
+
+    /// Index of the current frame, the first frame is frame 0.
+    #[inline]
+    pub fn frame(&self) -> u64
+    {
+        self.handle.frame()
+    }
+
+    /// Replaces the clock the timer measures frames with, e.g. with a `ManualClock`
+    /// for deterministic runs. Takes effect with the running frame.
+    pub fn set_time_source(&self, source: impl TimeSource + 'static)
+    {
+        self.handle.set_source(Arc::new(source));
+    }
+
+    /// Time since the engine was initialized, or since the time source was created if it was replaced.
+    #[inline]
+    pub fn elapsed(&self) -> Duration
+    {
+        self.handle.elapsed()
+    }
 
-            if delta < target
-            {
-                std::thread::sleep(target-delta);
-            }
+    /// Game time passed since the engine was initialized, as of the start of the frame.
+    #[inline]
+    pub fn game_elapsed(&self) -> Duration
+    {
+        self.handle.game_elapsed()
+    }
+
+    pub fn elapsed_of(&self, clock: Clock) -> Duration
+    {
+        match clock
+        {
+            Clock::Real => self.elapsed(),
+            Clock::Game => self.game_elapsed(),
         }
     }
 
-    pub fn set_target_fps(&self, target: Option<u64>)
+    /// Speed of the game clock relative to real time, 0.5 is half speed.
+    /// Negative scales are clamped to zero.
+    pub fn set_time_scale(&self, scale: f64)
+    {
+        self.handle.set_time_scale(scale);
+    }
+
+    #[inline]
+    pub fn time_scale(&self) -> f64
+    {
+        self.handle.time_scale()
+    }
+
+    /// Stops the game clock from the next frame on,
+    /// receivers on the game clock tick with a delta of zero and fixed ticks stop.
+    pub fn pause(&self)
+    {
+        self.handle.set_game_paused(true);
+    }
+
+    pub fn resume(&self)
+    {
+        self.handle.set_game_paused(false);
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool
+    {
+        self.handle.is_game_paused()
+    }
+
+    /// Starts a timer running on game time.
+    /// Every time it fires, `EventReceiver::timer_fired` is called with its id,
+    /// alternatively its status can be polled with `timer_status`.
+    pub fn schedule(&self, schedule: Schedule) -> TimerId
+    {
+        self.handle.schedule(schedule)
+    }
+
+    /// Shorthand for `schedule(Schedule::Once(delay))`.
+    pub fn after(&self, delay: Duration) -> TimerId
+    {
+        self.schedule(Schedule::Once(delay))
+    }
+
+    /// Shorthand for `schedule(Schedule::Every(period))`.
+    pub fn every(&self, period: Duration) -> TimerId
+    {
+        self.schedule(Schedule::Every(period))
+    }
+
+    /// Shorthand for `schedule(Schedule::Frames(frames))`.
+    pub fn after_frames(&self, frames: u64) -> TimerId
+    {
+        self.schedule(Schedule::Frames(frames))
+    }
+
+    /// Stops and forgets the timer, it will not fire anymore.
+    pub fn cancel_timer(&self, id: TimerId)
+    {
+        self.handle.cancel_timer(id);
+    }
+
+    pub fn pause_timer(&self, id: TimerId)
+    {
+        self.handle.set_timer_paused(id, true);
+    }
+
+    pub fn resume_timer(&self, id: TimerId)
+    {
+        self.handle.set_timer_paused(id, false);
+    }
+
+    /// Starts the timer over, a paused timer stays paused.
+    pub fn reset_timer(&self, id: TimerId)
+    {
+        self.handle.reset_timer(id);
+    }
+
+    /// `None` once the timer was cancelled or a frame after it finished.
+    pub fn timer_status(&self, id: TimerId) -> Option<TimerStatus>
+    {
+        self.handle.timer_status(id)
+    }
+
+    /// Length of a fixed tick in game time, 60 Hz by default.
+    #[inline]
+    pub fn fixed_step(&self) -> Duration
     {
-        if let Some(fps) = target
+        self.handle.fixed_step()
+    }
+
+    /// Sets the length of a fixed tick, a zero step is ignored.
+    pub fn set_fixed_step(&self, step: Duration)
+    {
+        self.handle.set_fixed_step(step);
+    }
+
+    /// Sets the fixed tick rate in Hz, a rate of zero is ignored.
+    pub fn set_fixed_rate(&self, hz: u32)
+    {
+        if hz != 0
         {
-            let ft = (1000u64).div_floor(fps);
-            self.handle.set_target_frame_time(Some(Duration::from_millis(ft)))
+            self.handle.set_fixed_step(Duration::from_secs(1) / hz);
         }
+    }
+
+    #[inline]
+    pub fn max_fixed_steps(&self) -> u32
+    {
+        self.handle.max_fixed_steps()
+    }
+
+    /// Caps the fixed ticks per frame, at least one tick is allowed.
+    /// A slow frame which would need more ticks drops the surplus time,
+    /// so the simulation slows down instead of falling further behind.
+    pub fn set_max_fixed_steps(&self, max_steps: u32)
+    {
+        self.handle.set_max_fixed_steps(max_steps);
+    }
+
+    /// How far the time is into the next fixed tick, from 0 to 1.
+    /// Rendering can blend between the last two simulation states with it.
+    #[inline]
+    pub fn fixed_alpha(&self) -> f32
+    {
+        self.handle.fixed_alpha()
+    }
+
+    /// Number of fixed ticks due this frame.
+    pub(crate) fn advance_fixed_step(&self) -> u32
+    {
+        self.handle.advance_fixed_step()
+    }
+
+    pub(crate) fn pad_frame_time(&self)
+    {
+        self.handle.pad_frame(self.target_frame_time());
+    }
+
+    /// Pads the frame to at least `HIDDEN_FRAME_TIME`, used while no window is visible.
+    /// Timers and input keep being processed, just without burning a core on frames nobody sees.
+    pub(crate) fn pad_hidden_frame(&self)
+    {
+        let target = self.target_frame_time().map_or(HIDDEN_FRAME_TIME, |target| target.max(HIDDEN_FRAME_TIME));
+        self.handle.pad_frame(Some(target));
+    }
+
+    /// Caps the frame rate, `None` or 0 removes the cap.
+    pub fn set_target_fps(&self, target: Option<u64>)
+    {
+        self.handle.set_pacing(FramePacing::from_fps(target.unwrap_or(0)));
+    }
+
+    /// Sets the minimum frame time, `None` removes the cap.
+    pub fn set_target_frame_time(&self, target: Option<Duration>)
+    {
+        self.handle.set_pacing(target.map_or(FramePacing::Unlimited, FramePacing::FrameTime));
+    }
+
+    pub fn set_pacing(&self, pacing: FramePacing)
+    {
+        self.handle.set_pacing(pacing);
+    }
 
-        else
+    #[inline]
+    pub fn pacing(&self) -> FramePacing
+    {
+        self.handle.pacing()
+    }
+
+    /// The frame time the current pacing aims for, `None` while unlimited.
+    pub fn target_frame_time(&self) -> Option<Duration>
+    {
+        match self.handle.pacing()
         {
-            self.handle.set_target_frame_time(None);
+            FramePacing::Unlimited => None,
+            FramePacing::FrameTime(target) => Some(target),
+
+            FramePacing::DisplayRefresh => {
+                let hz = get_v39().window().refresh_rate()
+                    .filter(|hz| *hz > 0.0)
+                    .unwrap_or(60.0);
+
+                Some(Duration::from_secs_f64(1.0 / hz))
+            },
         }
     }
+
+    /// How long before the end of a frame the timer stops sleeping and spins instead.
+    /// Larger values are more precise but burn more CPU, zero only sleeps.
+    pub fn set_spin_threshold(&self, threshold: Duration)
+    {
+        self.handle.set_spin_threshold(threshold);
+    }
+
+    #[inline]
+    pub fn spin_threshold(&self) -> Duration
+    {
+        self.handle.spin_threshold()
+    }
+
+    /// How closely the last frames met the target frame time.
+    pub fn pacing_stats(&self) -> PacingStats
+    {
+        self.handle.pacing_stats()
+    }
+
+    /// FPS, frame time percentiles and budget overruns of the last frames.
+    /// The budget is the target frame time, see `set_target_fps` and `set_pacing`.
+    pub fn frame_stats(&self) -> FrameStats
+    {
+        self.handle.frame_stats()
+    }
+
+    /// Writes the frames behind `frame_stats` as CSV, one row per frame.
+    pub fn write_frame_stats_csv(&self, mut out: impl std::io::Write) -> V39Result<()>
+    {
+        self.handle.write_frame_stats_csv(&mut out)?;
+        Ok(())
+    }
+
+    /// Writes the frames behind `frame_stats` to a CSV file, replacing it if it exists.
+    pub fn dump_frame_stats_csv(&self, path: impl AsRef<std::path::Path>) -> V39Result<()>
+    {
+        let file = std::fs::File::create(path)?;
+        self.write_frame_stats_csv(std::io::BufWriter::new(file))
+    }
 }
 
 
@@ -59,7 +325,18 @@ impl EventReceiver for TimerInterface
     {
         self.handle.end_frame_tracker();
         self.handle.start_frame_tracker();
+
+        for id in self.handle.advance_timers()
+        {
+            get_v39().event_handler().queue_engine_event(EngineEvent::TimerFired(Some(id)));
+        }
         Ok(())
     } 
+
+    fn frame_end(&mut self) -> V39Result<()>
+    {
+        self.handle.advance_frame();
+        Ok(())
+    }
 }
 
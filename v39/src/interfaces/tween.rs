@@ -0,0 +1,82 @@
+use crate::tween::{TweenManager, TweenId, TweenState, Animation, LoopMode, DEFAULT_CHANNEL};
+use crate::event::EngineEvent;
+use crate::prelude::*;
+use std::time::Duration;
+
+
+/// Plays animations on game time, they pause with the game clock.
+/// The values are advanced before the receivers added by the app tick.
+#[derive(Clone)]
+pub struct TweenInterface
+{
+    handle: &'static TweenManager,
+}
+
+
+impl TweenInterface
+{
+    pub(crate) fn new() -> V39Result<Self>
+    {
+        let handle = TweenManager::init()?;
+        info!("Tween Manager Initialized");
+
+        Ok(Self {handle})
+    }
+
+    /// Starts an animation, `EventReceiver::tween_finished` is called once it ends.
+    /// Animations looping forever never finish.
+    pub fn play(&self, animation: impl Into<Animation>, mode: LoopMode) -> TweenId
+    {
+        self.handle.tweens().play(animation.into(), mode)
+    }
+
+    /// Stops and forgets the animation without a finish event.
+    pub fn stop(&self, id: TweenId)
+    {
+        self.handle.tweens().stop(id);
+    }
+
+    pub fn pause(&self, id: TweenId)
+    {
+        self.handle.tweens().set_paused(id, true);
+    }
+
+    pub fn resume(&self, id: TweenId)
+    {
+        self.handle.tweens().set_paused(id, false);
+    }
+
+    /// The current value of a channel, `None` if the animation has no such channel
+    /// or is gone, which it is a frame after it finished.
+    pub fn value_of(&self, id: TweenId, channel: &str) -> Option<f32>
+    {
+        self.handle.tweens().value(id, channel)
+    }
+
+    /// The current value of the default channel.
+    pub fn value(&self, id: TweenId) -> Option<f32>
+    {
+        self.value_of(id, DEFAULT_CHANNEL)
+    }
+
+    pub fn state(&self, id: TweenId) -> Option<TweenState>
+    {
+        self.handle.tweens().state(id)
+    }
+}
+
+
+impl EventReceiver for TweenInterface
+{
+    fn tick(&mut self, delta: f32) -> V39Result<()>
+    {
+        let finished = self.handle.tweens().advance(Duration::from_secs_f32(delta));
+
+        for id in finished
+        {
+            get_v39().event_handler().queue_engine_event(EngineEvent::TweenFinished(Some(id)));
+        }
+
+        Ok(())
+    }
+}
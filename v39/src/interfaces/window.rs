@@ -0,0 +1,268 @@
+use crate::prelude::*;
+use crate::window::{WindowManager, WindowRequest, WindowMessage, WindowChange, WindowId, WindowSettings};
+use crate::window::{CursorGrab, CursorIcon, Fullscreen, WindowIcon};
+use std::sync::Arc;
+use winit::event_loop::{EventLoopProxy, EventLoopWindowTarget};
+
+
+/// Handle to a single window.
+/// Changes to the window are queued and applied by the event loop,
+/// so they take effect shortly after the call returns.
+#[derive(Clone)]
+pub struct WindowInterface
+{
+    handle: &'static WindowManager,
+    id: WindowId,
+}
+
+
+impl WindowInterface
+{
+    pub(crate) fn new(window: Arc<winit::window::Window>, proxy: EventLoopProxy<WindowMessage>) -> V39Result<Self>
+    {
+        let handle = WindowManager::init(window, proxy)?;
+        info!("Window Manager Initialized");
+
+        Ok(Self {handle, id: WindowId::MAIN})
+    }
+
+    pub(crate) fn open(&self, settings: WindowSettings) -> WindowInterface
+    {
+        let id = self.handle.open(settings);
+        Self {handle: self.handle, id}
+    }
+
+    /// Handle to another window, `None` if it was closed.
+    pub(crate) fn get(&self, id: WindowId) -> Option<WindowInterface>
+    {
+        self.handle.contains(id).then_some(Self {handle: self.handle, id})
+    }
+
+    pub(crate) fn ids(&self) -> Vec<WindowId>
+    {
+        self.handle.ids()
+    }
+
+    pub(crate) fn wake_event_loop(&self)
+    {
+        self.request(WindowRequest::Wake);
+    }
+
+    pub(crate) fn lookup(&self, winit_id: winit::window::WindowId) -> Option<WindowId>
+    {
+        self.handle.lookup(winit_id)
+    }
+
+    pub(crate) fn apply(&self, message: WindowMessage, target: &EventLoopWindowTarget<WindowMessage>) -> Option<WindowChange>
+    {
+        self.handle.apply(message, target)
+    }
+
+    pub fn id(&self) -> WindowId
+    {
+        self.id
+    }
+
+    /// Whether the window is still open, the main window is open until the app quits.
+    pub fn is_open(&self) -> bool
+    {
+        self.handle.contains(self.id)
+    }
+
+    /// Closes a window opened with `App::open_window`, the main window can not be closed.
+    pub fn close(&self)
+    {
+        self.request(WindowRequest::Close);
+    }
+
+    fn request(&self, request: WindowRequest)
+    {
+        self.handle.request(self.id, request);
+    }
+
+    pub fn set_title(&self, title: impl Into<String>)
+    {
+        self.request(WindowRequest::Title(title.into()));
+    }
+
+    pub fn title(&self) -> String
+    {
+        self.handle.title(self.id)
+    }
+
+    /// Requests a new inner size in physical pixels, the window system may pick a different one.
+    pub fn set_size(&self, size: (u32, u32))
+    {
+        self.request(WindowRequest::Size(size));
+    }
+
+    /// Inner size in physical pixels as of the last resize.
+    pub fn size(&self) -> (u32, u32)
+    {
+        self.handle.size(self.id)
+    }
+
+    pub fn set_min_size(&self, size: Option<(u32, u32)>)
+    {
+        self.request(WindowRequest::MinSize(size));
+    }
+
+    pub fn min_size(&self) -> Option<(u32, u32)>
+    {
+        self.handle.min_size(self.id)
+    }
+
+    pub fn set_max_size(&self, size: Option<(u32, u32)>)
+    {
+        self.request(WindowRequest::MaxSize(size));
+    }
+
+    pub fn max_size(&self) -> Option<(u32, u32)>
+    {
+        self.handle.max_size(self.id)
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: Fullscreen)
+    {
+        self.request(WindowRequest::Fullscreen(fullscreen));
+    }
+
+    pub fn fullscreen(&self) -> Fullscreen
+    {
+        self.handle.fullscreen(self.id)
+    }
+
+    pub fn set_maximized(&self, maximized: bool)
+    {
+        self.request(WindowRequest::Maximized(maximized));
+    }
+
+    /// Whether the window was last requested to be maximized.
+    pub fn is_maximized(&self) -> bool
+    {
+        self.handle.maximized(self.id)
+    }
+
+    pub fn set_minimized(&self, minimized: bool)
+    {
+        self.request(WindowRequest::Minimized(minimized));
+    }
+
+    /// Whether the window is minimized, as last requested or reported by the window system.
+    pub fn is_minimized(&self) -> bool
+    {
+        self.handle.minimized(self.id)
+    }
+
+    pub fn set_decorations(&self, decorations: bool)
+    {
+        self.request(WindowRequest::Decorations(decorations));
+    }
+
+    pub fn has_decorations(&self) -> bool
+    {
+        self.handle.decorations(self.id)
+    }
+
+    pub fn set_always_on_top(&self, on_top: bool)
+    {
+        self.request(WindowRequest::AlwaysOnTop(on_top));
+    }
+
+    pub fn is_always_on_top(&self) -> bool
+    {
+        self.handle.always_on_top(self.id)
+    }
+
+    /// Sets the icon shown in the title bar and task bar, `None` restores the default.
+    pub fn set_icon(&self, icon: Option<WindowIcon>)
+    {
+        self.request(WindowRequest::Icon(icon));
+    }
+
+    /// Refresh rate in Hz of the monitor the window is on, `None` if the platform does not report it.
+    pub fn refresh_rate(&self) -> Option<f64>
+    {
+        self.handle.refresh_rate(self.id).map(|mhz| mhz as f64 / 1000.0)
+    }
+
+    pub(crate) fn update_monitor(&self, id: WindowId)
+    {
+        self.handle.update_monitor(id);
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool)
+    {
+        self.request(WindowRequest::CursorVisible(visible));
+    }
+
+    pub fn is_cursor_visible(&self) -> bool
+    {
+        self.handle.cursor_visible(self.id)
+    }
+
+    /// Confines or locks the cursor to the window.
+    /// Platforms which lack the requested mode fall back to the other one.
+    pub fn set_cursor_grab(&self, grab: CursorGrab)
+    {
+        self.request(WindowRequest::CursorGrab(grab));
+    }
+
+    /// The requested grab mode, the grab is given up while the window is unfocused.
+    pub fn cursor_grab(&self) -> CursorGrab
+    {
+        self.handle.cursor_grab(self.id)
+    }
+
+    /// Moves the cursor to a position in physical pixels relative to the top left corner of the window.
+    pub fn set_cursor_position(&self, position: (f64, f64))
+    {
+        self.request(WindowRequest::CursorPosition(position));
+    }
+
+    pub fn set_cursor(&self, icon: CursorIcon)
+    {
+        self.request(WindowRequest::Cursor(icon));
+    }
+
+    pub fn cursor(&self) -> CursorIcon
+    {
+        self.handle.cursor(self.id)
+    }
+}
+
+
+impl EventReceiver for WindowInterface
+{
+    fn window_resize(&mut self, size: (u32, u32)) -> V39Result<()>
+    {
+        self.handle.set_size(get_v39().event_handler().event_window(), size);
+        Ok(())
+    }
+
+    fn window_minimize(&mut self) -> V39Result<()>
+    {
+        self.handle.set_minimized(get_v39().event_handler().event_window(), true);
+        Ok(())
+    }
+
+    fn window_restore(&mut self) -> V39Result<()>
+    {
+        self.handle.set_minimized(get_v39().event_handler().event_window(), false);
+        Ok(())
+    }
+
+    fn window_focus(&mut self) -> V39Result<()>
+    {
+        // Some platforms release the grab when the window loses focus
+        let id = get_v39().event_handler().event_window();
+        let grab = self.handle.cursor_grab(id);
+
+        if grab != CursorGrab::None
+        {
+            self.handle.request(id, WindowRequest::CursorGrab(grab));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,510 @@
+pub mod sinks;
+pub use sinks::{ConsoleSink, FileSink, LogBuffer};
+pub use log::{Level, LevelFilter};
+
+use once_cell::sync::OnceCell;
+use log::{Metadata, Record};
+use std::sync::Mutex;
+use crate::prelude::*;
+
+
+static INSTANCE: OnceCell<Logger> = OnceCell::new();
+
+/// Lines kept by the engine's log buffer, see `LoggerInterface::buffer`.
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 200;
+
+
+/// A log line as it is handed to the sinks.
+#[derive(Clone, Debug)]
+pub struct LogEntry
+{
+    pub level: Level,
+    pub time: chrono::DateTime<chrono::Local>,
+
+    /// Module path of the call site, unless a target was given to the log macro.
+    pub target: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+
+impl LogEntry
+{
+    pub(crate) fn from_record(record: &Record) -> Self
+    {
+        Self {
+            level: record.level(),
+            time: chrono::Local::now(),
+            target: record.target().into(),
+            message: record.args().to_string(),
+            file: record.file().map(String::from),
+            line: record.line(),
+        }
+    }
+
+    pub fn format(&self, format: LogFormat) -> String
+    {
+        self.format_level(format, &self.level.to_string())
+    }
+
+    /// Formats the entry with `level` in place of the level name, e.g. to color it.
+    pub(crate) fn format_level(&self, format: LogFormat, level: &str) -> String
+    {
+        let time = self.time.format("%H:%M:%S%.3f");
+
+        match format
+        {
+            LogFormat::Compact => format!("[{level} - {time}]: {}", self.message),
+            LogFormat::Detailed => format!("[{level}({time}) - {}:{}]: {}",
+                self.file.as_deref().unwrap_or(""),
+                self.line.unwrap_or(0),
+                self.message),
+        }
+    }
+}
+
+
+/// How sinks which write text lay out a line.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LogFormat
+{
+    /// `[INFO - 12:00:00.000]: message`
+    Compact,
+
+    /// `[INFO(12:00:00.000) - src/app.rs:10]: message`
+    Detailed,
+}
+
+
+impl Default for LogFormat
+{
+    /// Detailed in debug builds, compact in release builds.
+    fn default() -> Self
+    {
+        match cfg!(debug_assertions)
+        {
+            true => LogFormat::Detailed,
+            false => LogFormat::Compact,
+        }
+    }
+}
+
+
+/// Receives every log entry which passes the filters.
+/// Sinks must not log themselves, the logger is busy while they write.
+pub trait LogSink: Send
+{
+    fn write(&mut self, entry: &LogEntry);
+
+    fn flush(&mut self) {}
+
+    /// Entries above this level are not handed to the sink, regardless of the filters.
+    fn level(&self) -> LevelFilter
+    {
+        LevelFilter::Trace
+    }
+}
+
+
+/// Handle to a sink added with `LoggerInterface::add_sink`.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, PartialOrd, Ord)]
+pub struct SinkId(u64);
+
+
+/// A default level and levels for modules and their submodules.
+/// The most specific module wins, e.g. `v39::renderer=warn` over `v39=debug`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogFilters
+{
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+
+impl Default for LogFilters
+{
+    /// Everything in debug builds, errors in release builds.
+    fn default() -> Self
+    {
+        match cfg!(debug_assertions)
+        {
+            true => Self::new(LevelFilter::Trace),
+            false => Self::new(LevelFilter::Error),
+        }
+    }
+}
+
+
+impl LogFilters
+{
+    pub fn new(default: LevelFilter) -> Self
+    {
+        Self {default, modules: vec![]}
+    }
+
+    /// Parses filters in the `RUST_LOG` syntax, e.g. `info,v39::renderer=warn,my_game=trace`.
+    /// A module without a level logs everything.
+    pub fn parse(filters: &str) -> V39Result<Self>
+    {
+        let mut parsed = Self::new(LevelFilter::Error);
+        let invalid = |item: &str| V39Error::config(format!("Invalid log filter {item:?}"));
+
+        for item in filters.split(',').map(str::trim).filter(|item| !item.is_empty())
+        {
+            match item.split_once('=')
+            {
+                Some((module, level)) => {
+                    let level = level.trim().parse().map_err(|_| invalid(item))?;
+                    let module = module.trim();
+
+                    if module.is_empty() {return Err(invalid(item))}
+                    parsed.set_module(module, level);
+                },
+
+                None => match item.parse()
+                {
+                    Ok(level) => parsed.default = level,
+                    Err(_) => parsed.set_module(item, LevelFilter::Trace),
+                },
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn set_default(&mut self, level: LevelFilter)
+    {
+        self.default = level;
+    }
+
+    pub fn set_module(&mut self, module: impl Into<String>, level: LevelFilter)
+    {
+        let module = module.into();
+
+        match self.modules.iter_mut().find(|(m, _)| *m == module)
+        {
+            Some((_, current)) => *current = level,
+            None => self.modules.push((module, level)),
+        }
+    }
+
+    pub fn level_for(&self, target: &str) -> LevelFilter
+    {
+        let within = |module: &str| target == module || target.strip_prefix(module).is_some_and(|rest| rest.starts_with("::"));
+
+        self.modules.iter()
+            .filter(|(module, _)| within(module))
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool
+    {
+        level <= self.level_for(target)
+    }
+
+    /// The most verbose level any module logs at.
+    pub fn max_level(&self) -> LevelFilter
+    {
+        self.modules.iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+
+/// Logging settings of the engine config.
+pub struct LogConfig
+{
+    filters: LogFilters,
+    console: Option<ConsoleSink>,
+    buffer_capacity: usize,
+    sinks: Vec<Box<dyn LogSink>>,
+}
+
+
+impl Default for LogConfig
+{
+    /// Logs to stderr, filtered by `RUST_LOG` if it is set.
+    /// Setting `V39_DISCARD_LOG` turns the console output off.
+    fn default() -> Self
+    {
+        let filters = match std::env::var("RUST_LOG")
+        {
+            Ok(filters) => LogFilters::parse(&filters).unwrap_or_else(|e| {
+                eprintln!("Ignoring RUST_LOG: {e}");
+                LogFilters::default()
+            }),
+
+            Err(_) => LogFilters::default(),
+        };
+
+        let console = match std::env::var("V39_DISCARD_LOG")
+        {
+            Ok(_) => None,
+            Err(_) => Some(ConsoleSink::new()),
+        };
+
+        Self {filters, console, buffer_capacity: DEFAULT_BUFFER_CAPACITY, sinks: vec![]}
+    }
+}
+
+
+impl LogConfig
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Level of every module without its own filter.
+    pub fn level(mut self, level: LevelFilter) -> Self
+    {
+        self.filters.set_default(level);
+        self
+    }
+
+    /// Level of a module and its submodules, e.g. `("v39::renderer", LevelFilter::Warn)`.
+    pub fn module(mut self, module: impl Into<String>, level: LevelFilter) -> Self
+    {
+        self.filters.set_module(module, level);
+        self
+    }
+
+    /// Replaces all filters, including the ones taken from `RUST_LOG`.
+    pub fn filters(mut self, filters: LogFilters) -> Self
+    {
+        self.filters = filters;
+        self
+    }
+
+    /// The sink writing to stderr, `None` to turn it off.
+    pub fn console(mut self, console: Option<ConsoleSink>) -> Self
+    {
+        self.console = console;
+        self
+    }
+
+    /// Lines kept in memory for consoles and crash reports, 200 by default.
+    pub fn buffer_capacity(mut self, lines: usize) -> Self
+    {
+        self.buffer_capacity = lines;
+        self
+    }
+
+    /// Adds a sink, every sink receives every entry which passes the filters.
+    pub fn sink(mut self, sink: impl LogSink + 'static) -> Self
+    {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+}
+
+
+#[derive(Default)]
+struct Sinks
+{
+    sinks: Vec<(SinkId, Box<dyn LogSink>)>,
+    next_id: u64,
+}
+
+
+impl Sinks
+{
+    fn add(&mut self, sink: Box<dyn LogSink>) -> SinkId
+    {
+        let id = SinkId(self.next_id);
+        self.next_id += 1;
+
+        self.sinks.push((id, sink));
+        id
+    }
+}
+
+
+pub(crate) struct Logger
+{
+    filters: Mutex<LogFilters>,
+    sinks: Mutex<Sinks>,
+    buffer: LogBuffer,
+}
+
+
+impl Logger
+{
+    /// Installs the logger as the one of the `log` crate.
+    pub(crate) fn init(config: LogConfig) -> V39Result<&'static Self>
+    {
+        let buffer = LogBuffer::new(config.buffer_capacity);
+        let mut sinks = Sinks::default();
+
+        sinks.add(Box::new(buffer.clone()));
+
+        if let Some(console) = config.console
+        {
+            sinks.add(Box::new(console));
+        }
+
+        for sink in config.sinks
+        {
+            sinks.add(sink);
+        }
+
+        let max_level = config.filters.max_level();
+        let logger = Logger {filters: Mutex::new(config.filters), sinks: Mutex::new(sinks), buffer};
+
+        if INSTANCE.set(logger).is_err()
+        {
+            return Err(V39Error::Reinit("Logger".into()));
+        }
+
+        let logger = INSTANCE.get().unwrap();
+
+        log::set_logger(logger).map_err(|_| V39Error::ForeignLogger)?;
+        log::set_max_level(max_level);
+
+        Ok(logger)
+    }
+
+    pub(crate) fn get() -> Option<&'static Self>
+    {
+        INSTANCE.get()
+    }
+
+    pub(crate) fn buffer(&self) -> &LogBuffer
+    {
+        &self.buffer
+    }
+
+    pub(crate) fn add_sink(&self, sink: Box<dyn LogSink>) -> SinkId
+    {
+        self.sinks.lock().unwrap().add(sink)
+    }
+
+    pub(crate) fn remove_sink(&self, id: SinkId)
+    {
+        if let Ok(mut sinks) = self.sinks.lock()
+        {
+            if let Some(index) = sinks.sinks.iter().position(|(sink, _)| *sink == id)
+            {
+                let (_, mut sink) = sinks.sinks.remove(index);
+                sink.flush();
+            }
+        }
+    }
+
+    /// Flushes the sinks unless they are in use, e.g. by a panicking sink.
+    pub(crate) fn try_flush(&self)
+    {
+        if let Ok(mut sinks) = self.sinks.try_lock()
+        {
+            for (_, sink) in sinks.sinks.iter_mut()
+            {
+                sink.flush();
+            }
+        }
+    }
+
+    pub(crate) fn filters(&self) -> LogFilters
+    {
+        self.filters.lock().unwrap().clone()
+    }
+
+    pub(crate) fn update_filters(&self, f: impl FnOnce(&mut LogFilters))
+    {
+        let mut filters = self.filters.lock().unwrap();
+        f(&mut filters);
+
+        log::set_max_level(filters.max_level());
+    }
+}
+
+
+impl log::Log for Logger
+{
+    fn enabled(&self, metadata: &Metadata) -> bool
+    {
+        self.filters.lock()
+            .map(|filters| filters.enabled(metadata.target(), metadata.level()))
+            .unwrap_or(false)
+    }
+
+    fn log(&self, record: &Record)
+    {
+        if !self.enabled(record.metadata()) {return}
+
+        let entry = LogEntry::from_record(record);
+
+        if let Ok(mut sinks) = self.sinks.lock()
+        {
+            for (_, sink) in sinks.sinks.iter_mut()
+            {
+                if entry.level <= sink.level()
+                {
+                    sink.write(&entry);
+                }
+            }
+        }
+    }
+
+    fn flush(&self)
+    {
+        if let Ok(mut sinks) = self.sinks.lock()
+        {
+            for (_, sink) in sinks.sinks.iter_mut()
+            {
+                sink.flush();
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn most_specific_module_wins()
+    {
+        let filters = LogFilters::parse("info, v39=debug, v39::renderer=warn, my_game").unwrap();
+
+        assert_eq!(filters.level_for("v39::renderer::device"), LevelFilter::Warn);
+        assert_eq!(filters.level_for("v39::timer"), LevelFilter::Debug);
+        assert_eq!(filters.level_for("v39"), LevelFilter::Debug);
+        assert_eq!(filters.level_for("v39_extra"), LevelFilter::Info);
+        assert_eq!(filters.level_for("my_game::ui"), LevelFilter::Trace);
+        assert_eq!(filters.max_level(), LevelFilter::Trace);
+
+        assert!(filters.enabled("v39::renderer", Level::Error));
+        assert!(!filters.enabled("v39::renderer", Level::Info));
+    }
+
+    #[test]
+    fn invalid_filters()
+    {
+        assert!(LogFilters::parse("v39=loud").is_err());
+        assert!(LogFilters::parse("=info").is_err());
+        assert_eq!(LogFilters::parse("").unwrap(), LogFilters::new(LevelFilter::Error));
+    }
+
+    #[test]
+    fn formats()
+    {
+        let entry = LogEntry {
+            level: Level::Warn,
+            time: chrono::Local::now(),
+            target: "v39::timer".into(),
+            message: "Frame took too long".into(),
+            file: Some("src/timer/mod.rs".into()),
+            line: Some(42),
+        };
+
+        let time = entry.time.format("%H:%M:%S%.3f");
+
+        assert_eq!(entry.format(LogFormat::Compact), format!("[WARN - {time}]: Frame took too long"));
+        assert_eq!(entry.format(LogFormat::Detailed), format!("[WARN({time}) - src/timer/mod.rs:42]: Frame took too long"));
+    }
+}
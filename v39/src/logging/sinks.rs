@@ -0,0 +1,451 @@
+use log::{Level, LevelFilter};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use super::{LogEntry, LogFormat, LogSink};
+use crate::prelude::*;
+
+
+/// Writes to stderr, with colored levels if stderr is a terminal.
+#[derive(Clone, Debug)]
+pub struct ConsoleSink
+{
+    format: LogFormat,
+    level: LevelFilter,
+    color: bool,
+}
+
+
+impl Default for ConsoleSink
+{
+    fn default() -> Self
+    {
+        Self {format: LogFormat::default(), level: LevelFilter::Trace, color: std::io::stderr().is_terminal()}
+    }
+}
+
+
+impl ConsoleSink
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn format(mut self, format: LogFormat) -> Self
+    {
+        self.format = format;
+        self
+    }
+
+    pub fn level(mut self, level: LevelFilter) -> Self
+    {
+        self.level = level;
+        self
+    }
+
+    pub fn color(mut self, color: bool) -> Self
+    {
+        self.color = color;
+        self
+    }
+}
+
+
+impl LogSink for ConsoleSink
+{
+    fn write(&mut self, entry: &LogEntry)
+    {
+        let line = match self.color
+        {
+            true => {
+                let color = match entry.level
+                {
+                    Level::Error => 31,
+                    Level::Warn => 33,
+                    Level::Info => 32,
+                    Level::Debug => 34,
+                    Level::Trace => 35,
+                };
+
+                entry.format_level(self.format, &format!("\x1b[{color}m{}\x1b[0m", entry.level))
+            },
+
+            false => entry.format(self.format),
+        };
+
+        let _ = writeln!(std::io::stderr().lock(), "{line}");
+    }
+
+    fn level(&self) -> LevelFilter
+    {
+        self.level
+    }
+}
+
+
+/// Appends to a log file, which is rotated once it would grow beyond `max_size`.
+/// Rotated files get a number appended, `game.log.1` is the most recent one.
+/// The sink stops writing after the first failure, which is reported to stderr once.
+pub struct FileSink
+{
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    format: LogFormat,
+    level: LevelFilter,
+    file: Option<BufWriter<File>>,
+    size: u64,
+    failed: bool,
+}
+
+
+impl FileSink
+{
+    pub const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+    pub const DEFAULT_MAX_FILES: usize = 5;
+
+    /// Opens or creates the file, the directories leading to it are created as well.
+    pub fn new(path: impl AsRef<Path>) -> V39Result<Self>
+    {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(dir).with_context(|| format!("Creating the log directory {}", dir.display()))?;
+        }
+
+        let file = File::options().create(true).append(true).open(&path)
+            .with_context(|| format!("Opening the log file {}", path.display()))?;
+
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_size: Self::DEFAULT_MAX_SIZE,
+            max_files: Self::DEFAULT_MAX_FILES,
+            format: LogFormat::Detailed,
+            level: LevelFilter::Trace,
+            file: Some(BufWriter::new(file)),
+            size,
+            failed: false,
+        })
+    }
+
+    /// Size in bytes the file is rotated at, 10 MiB by default.
+    pub fn max_size(mut self, bytes: u64) -> Self
+    {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Rotated files kept besides the current one, 5 by default.
+    /// With 0 the file is truncated instead of rotated.
+    pub fn max_files(mut self, files: usize) -> Self
+    {
+        self.max_files = files;
+        self
+    }
+
+    /// Detailed by default.
+    pub fn format(mut self, format: LogFormat) -> Self
+    {
+        self.format = format;
+        self
+    }
+
+    pub fn level(mut self, level: LevelFilter) -> Self
+    {
+        self.level = level;
+        self
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf
+    {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()>
+    {
+        if let Some(mut file) = self.file.take()
+        {
+            file.flush()?;
+        }
+
+        if self.max_files > 0
+        {
+            let oldest = self.rotated(self.max_files);
+
+            if oldest.exists()
+            {
+                std::fs::remove_file(oldest)?;
+            }
+
+            for index in (1..self.max_files).rev()
+            {
+                let from = self.rotated(index);
+
+                if from.exists()
+                {
+                    std::fs::rename(from, self.rotated(index + 1))?;
+                }
+            }
+
+            std::fs::rename(&self.path, self.rotated(1))?;
+        }
+
+        self.file = Some(BufWriter::new(File::create(&self.path)?));
+        self.size = 0;
+
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str, flush: bool) -> std::io::Result<()>
+    {
+        let len = line.len() as u64 + 1;
+
+        if self.size > 0 && self.size + len > self.max_size
+        {
+            self.rotate()?;
+        }
+
+        if let Some(file) = &mut self.file
+        {
+            writeln!(file, "{line}")?;
+            self.size += len;
+
+            if flush {file.flush()?}
+        }
+
+        Ok(())
+    }
+}
+
+
+impl LogSink for FileSink
+{
+    /// Warnings and errors are flushed right away, they are the ones needed after a crash.
+    fn write(&mut self, entry: &LogEntry)
+    {
+        if self.failed {return}
+
+        let line = entry.format(self.format);
+
+        if let Err(e) = self.write_line(&line, entry.level <= Level::Warn)
+        {
+            // Logging the failure would end up right back here
+            eprintln!("Failed to write to the log file {}, no further entries are written to it: {e}", self.path.display());
+            self.failed = true;
+            self.file = None;
+        }
+    }
+
+    fn flush(&mut self)
+    {
+        if let Some(file) = &mut self.file
+        {
+            let _ = file.flush();
+        }
+    }
+
+    fn level(&self) -> LevelFilter
+    {
+        self.level
+    }
+}
+
+
+/// Keeps the last entries in memory, e.g. for an in-game console.
+/// Clones share the entries, keep one to read what the sink received.
+#[derive(Clone)]
+pub struct LogBuffer
+{
+    ring: Arc<Mutex<Ring>>,
+    level: LevelFilter,
+}
+
+
+struct Ring
+{
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+}
+
+
+impl LogBuffer
+{
+    pub fn new(capacity: usize) -> Self
+    {
+        Self {ring: Arc::new(Mutex::new(Ring {entries: VecDeque::new(), capacity})), level: LevelFilter::Trace}
+    }
+
+    /// Only applies to the sink added after the call, not to existing clones.
+    pub fn level(mut self, level: LevelFilter) -> Self
+    {
+        self.level = level;
+        self
+    }
+
+    /// The kept entries, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry>
+    {
+        self.ring.lock().unwrap().entries.iter().cloned().collect()
+    }
+
+    pub fn lines(&self, format: LogFormat) -> Vec<String>
+    {
+        self.ring.lock().unwrap().entries.iter().map(|e| e.format(format)).collect()
+    }
+
+    /// Like `lines`, but gives up instead of waiting for the lock.
+    pub(crate) fn try_lines(&self, format: LogFormat) -> Option<Vec<String>>
+    {
+        let ring = self.ring.try_lock().ok()?;
+        Some(ring.entries.iter().map(|e| e.format(format)).collect())
+    }
+
+    pub fn capacity(&self) -> usize
+    {
+        self.ring.lock().unwrap().capacity
+    }
+
+    pub fn set_capacity(&self, capacity: usize)
+    {
+        let mut ring = self.ring.lock().unwrap();
+        ring.capacity = capacity;
+
+        while ring.entries.len() > capacity
+        {
+            ring.entries.pop_front();
+        }
+    }
+
+    pub fn clear(&self)
+    {
+        self.ring.lock().unwrap().entries.clear();
+    }
+}
+
+
+impl LogSink for LogBuffer
+{
+    fn write(&mut self, entry: &LogEntry)
+    {
+        if let Ok(mut ring) = self.ring.lock()
+        {
+            if ring.capacity == 0 {return}
+
+            while ring.entries.len() >= ring.capacity
+            {
+                ring.entries.pop_front();
+            }
+
+            ring.entries.push_back(entry.clone());
+        }
+    }
+
+    fn level(&self) -> LevelFilter
+    {
+        self.level
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn entry(message: &str) -> LogEntry
+    {
+        LogEntry {
+            level: Level::Info,
+            time: chrono::Local::now(),
+            target: "tests".into(),
+            message: message.into(),
+            file: None,
+            line: None,
+        }
+    }
+
+    #[test]
+    fn buffer_keeps_the_last_entries()
+    {
+        let buffer = LogBuffer::new(2);
+        let mut sink = buffer.clone();
+
+        for message in ["a", "b", "c"]
+        {
+            sink.write(&entry(message));
+        }
+
+        assert_eq!(buffer.entries().iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+
+        buffer.set_capacity(1);
+        assert_eq!(buffer.entries()[0].message, "c");
+    }
+
+    #[test]
+    fn file_rotation()
+    {
+        let dir = std::env::temp_dir().join(format!("v39-log-rotation-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("game.log");
+
+        // Every line is 30 bytes, two fit into a file
+        let mut sink = FileSink::new(&path).unwrap()
+            .format(LogFormat::Compact)
+            .max_size(80)
+            .max_files(2);
+
+        for i in 0..7
+        {
+            sink.write(&entry(&format!("line {i}")));
+        }
+
+        sink.flush();
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap()
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(read("game.log"), ["6"]);
+        assert_eq!(read("game.log.1"), ["4", "5"]);
+        assert_eq!(read("game.log.2"), ["2", "3"]);
+        assert!(!dir.join("game.log.3").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_sink_stops_after_a_failed_rotation()
+    {
+        let dir = std::env::temp_dir().join(format!("v39-log-failure-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("game.log");
+
+        // The rotated file can not be removed to make room, it is a directory
+        std::fs::create_dir_all(dir.join("game.log.1")).unwrap();
+
+        let mut sink = FileSink::new(&path).unwrap()
+            .format(LogFormat::Compact)
+            .max_size(40)
+            .max_files(1);
+
+        sink.write(&entry("line 0"));
+        sink.write(&entry("line 1"));
+        assert!(sink.failed);
+
+        sink.write(&entry("line 2"));
+        sink.flush();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,13 @@
 use crate::interfaces::app::App;
 
 
-pub use crate::error::V39Error;
+pub use crate::error::{V39Error, ErrorContext};
 pub use crate::event::receiver::EventReceiver;
 pub use crate::event::event::{Event, EventData};
 pub use crate::input;
+pub use crate::quit::{QuitReason, ExitStatus};
+pub use crate::config::EngineConfig;
+pub use crate::logging::LogConfig;
 
 
 pub type V39Result<T> = Result<T, V39Error>;
@@ -0,0 +1,370 @@
+use once_cell::sync::OnceCell;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Instant, Duration};
+use crate::prelude::*;
+
+
+static INSTANCE: OnceCell<Profiler> = OnceCell::new();
+
+pub(crate) const DEFAULT_CAPACITY: usize = 300;
+
+
+/// Measures the rest of the enclosing block while the profiler is enabled,
+/// e.g. `v39::profile_scope!("Physics")`. The name is not evaluated while disabled.
+#[macro_export]
+macro_rules! profile_scope
+{
+    ($name:expr) => {
+        let _profile_scope = $crate::profiler::ProfileScope::new(|| $name);
+    };
+}
+
+
+thread_local!
+{
+    static THREAD_ID: u64 = {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    };
+}
+
+
+/// A measured scope, times are relative to the profiler start.
+#[derive(Clone, Debug)]
+pub struct ProfileSpan
+{
+    pub name: Cow<'static, str>,
+    pub thread: u64,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct ProfiledFrame
+{
+    pub index: u64,
+    pub start: Duration,
+    pub duration: Duration,
+
+    /// Spans in the order they ended, nested spans come before their parents.
+    pub spans: Vec<ProfileSpan>,
+}
+
+
+/// Records the scope from its creation until it is dropped.
+pub struct ProfileScope
+{
+    name: Option<Cow<'static, str>>,
+    start: Duration,
+}
+
+
+impl ProfileScope
+{
+    /// The name is only built while the profiler is enabled.
+    pub fn new<N: Into<Cow<'static, str>>>(name: impl FnOnce() -> N) -> Self
+    {
+        match Profiler::get().filter(|p| p.is_enabled())
+        {
+            Some(profiler) => Self {name: Some(name().into()), start: profiler.now()},
+            None => Self {name: None, start: Duration::ZERO},
+        }
+    }
+}
+
+
+impl Drop for ProfileScope
+{
+    fn drop(&mut self)
+    {
+        if let (Some(name), Some(profiler)) = (self.name.take(), Profiler::get())
+        {
+            let duration = profiler.now().saturating_sub(self.start);
+            let thread = THREAD_ID.with(|id| *id);
+
+            profiler.record(ProfileSpan {name, thread, start: self.start, duration});
+        }
+    }
+}
+
+
+/// The last frames, the oldest are dropped once `capacity` is reached.
+pub(crate) struct FrameRing
+{
+    frames: VecDeque<ProfiledFrame>,
+    current: Option<ProfiledFrame>,
+    capacity: usize,
+    next_index: u64,
+}
+
+
+impl FrameRing
+{
+    pub(crate) fn new(capacity: usize) -> Self
+    {
+        Self {frames: VecDeque::new(), current: None, capacity: capacity.max(1), next_index: 0}
+    }
+
+    pub(crate) fn begin_frame(&mut self, now: Duration)
+    {
+        self.end_frame(now);
+
+        self.current = Some(ProfiledFrame {index: self.next_index, start: now, duration: Duration::ZERO, spans: vec![]});
+        self.next_index += 1;
+    }
+
+    pub(crate) fn end_frame(&mut self, now: Duration)
+    {
+        if let Some(mut frame) = self.current.take()
+        {
+            frame.duration = now.saturating_sub(frame.start);
+
+            while self.frames.len() >= self.capacity
+            {
+                self.frames.pop_front();
+            }
+
+            self.frames.push_back(frame);
+        }
+    }
+
+    /// Spans outside of a frame are dropped.
+    pub(crate) fn record(&mut self, span: ProfileSpan)
+    {
+        if let Some(frame) = &mut self.current
+        {
+            frame.spans.push(span);
+        }
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize)
+    {
+        self.capacity = capacity.max(1);
+
+        while self.frames.len() > self.capacity
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    pub(crate) fn frames(&self) -> Vec<ProfiledFrame>
+    {
+        self.frames.iter().cloned().collect()
+    }
+
+    pub(crate) fn clear(&mut self)
+    {
+        self.frames.clear();
+        self.current = None;
+    }
+
+    /// Writes the finished frames in the Chrome Trace Event format,
+    /// which can be opened in Perfetto or chrome://tracing.
+    pub(crate) fn write_chrome_trace(&self, out: &mut impl Write) -> std::io::Result<()>
+    {
+        let micros = |d: Duration| d.as_secs_f64() * 1_000_000.0;
+        let mut first = true;
+
+        write!(out, "{{\"traceEvents\":[")?;
+
+        for frame in &self.frames
+        {
+            let frame_name = format!("Frame {}", frame.index);
+            let events = std::iter::once((frame_name.as_str(), 0, frame.start, frame.duration))
+                .chain(frame.spans.iter().map(|s| (s.name.as_ref(), s.thread, s.start, s.duration)));
+
+            for (name, thread, start, duration) in events
+            {
+                if !first {write!(out, ",")?}
+                first = false;
+
+                write!(out, "{{\"name\":\"{}\",\"cat\":\"v39\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":{}}}",
+                    escape_json(name),
+                    micros(start),
+                    micros(duration),
+                    thread)?;
+            }
+        }
+
+        writeln!(out, "],\"displayTimeUnit\":\"ms\"}}")
+    }
+}
+
+
+fn escape_json(s: &str) -> String
+{
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars()
+    {
+        match c
+        {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+
+pub(crate) struct Profiler
+{
+    start: Instant,
+    enabled: AtomicBool,
+    ring: Mutex<FrameRing>,
+}
+
+
+impl Profiler
+{
+    pub(crate) fn init() -> V39Result<&'static Self>
+    {
+        let profiler = Profiler {
+            start: Instant::now(),
+            enabled: AtomicBool::new(false),
+            ring: Mutex::new(FrameRing::new(DEFAULT_CAPACITY)),
+        };
+
+        if INSTANCE.set(profiler).is_err()
+        {
+            return Err(V39Error::Reinit("Profiler".into()));
+        }
+
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    fn get() -> Option<&'static Self>
+    {
+        INSTANCE.get()
+    }
+
+    fn now(&self) -> Duration
+    {
+        self.start.elapsed()
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool
+    {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool)
+    {
+        self.enabled.store(enabled, Ordering::Relaxed);
+
+        // Do not keep a half recorded frame around
+        if !enabled
+        {
+            if let Ok(mut ring) = self.ring.lock()
+            {
+                ring.current = None;
+            }
+        }
+    }
+
+    pub(crate) fn begin_frame(&self)
+    {
+        if !self.is_enabled() {return}
+
+        if let Ok(mut ring) = self.ring.lock()
+        {
+            ring.begin_frame(self.now());
+        }
+    }
+
+    pub(crate) fn end_frame(&self)
+    {
+        if let Ok(mut ring) = self.ring.lock()
+        {
+            ring.end_frame(self.now());
+        }
+    }
+
+    fn record(&self, span: ProfileSpan)
+    {
+        if let Ok(mut ring) = self.ring.lock()
+        {
+            ring.record(span);
+        }
+    }
+
+    pub(crate) fn ring(&self) -> std::sync::MutexGuard<'_, FrameRing>
+    {
+        self.ring.lock().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn span(name: &'static str, start: u64, duration: u64) -> ProfileSpan
+    {
+        ProfileSpan {name: name.into(), thread: 1, start: Duration::from_micros(start), duration: Duration::from_micros(duration)}
+    }
+
+    #[test]
+    fn ring_keeps_the_last_frames()
+    {
+        let mut ring = FrameRing::new(2);
+
+        for i in 0..3
+        {
+            ring.begin_frame(Duration::from_millis(i * 10));
+            ring.record(span("Tick", i * 10_000, 5));
+        }
+
+        ring.end_frame(Duration::from_millis(25));
+
+        let frames = ring.frames();
+
+        assert_eq!(frames.iter().map(|f| f.index).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(frames[1].duration, Duration::from_millis(5));
+        assert_eq!(frames[1].spans.len(), 1);
+    }
+
+    #[test]
+    fn spans_outside_of_frames_are_dropped()
+    {
+        let mut ring = FrameRing::new(4);
+
+        ring.record(span("Stray", 0, 1));
+        ring.begin_frame(Duration::ZERO);
+        ring.end_frame(Duration::from_millis(1));
+
+        assert!(ring.frames()[0].spans.is_empty());
+    }
+
+    #[test]
+    fn chrome_trace_format()
+    {
+        let mut ring = FrameRing::new(4);
+
+        ring.begin_frame(Duration::ZERO);
+        ring.record(span("Say \"hi\"", 10, 5));
+        ring.end_frame(Duration::from_micros(20));
+
+        let mut out = vec![];
+        ring.write_chrome_trace(&mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), concat!(
+            "{\"traceEvents\":[",
+            "{\"name\":\"Frame 0\",\"cat\":\"v39\",\"ph\":\"X\",\"ts\":0.000,\"dur\":20.000,\"pid\":1,\"tid\":0},",
+            "{\"name\":\"Say \\\"hi\\\"\",\"cat\":\"v39\",\"ph\":\"X\",\"ts\":10.000,\"dur\":5.000,\"pid\":1,\"tid\":1}",
+            "],\"displayTimeUnit\":\"ms\"}\n",
+        ));
+    }
+}
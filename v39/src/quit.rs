@@ -0,0 +1,235 @@
+use std::process::{ExitCode, Termination};
+use std::sync::Arc;
+use crate::prelude::*;
+
+
+/// Why the app quits, see `App::quit_with`.
+#[derive(Clone, Debug, Default)]
+pub struct QuitReason
+{
+    /// Exit code of the process, 0 for success.
+    pub code: i32,
+
+    /// The error the app quit because of, if any.
+    pub error: Option<Arc<V39Error>>,
+}
+
+
+impl QuitReason
+{
+    pub fn success() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn with_code(code: i32) -> Self
+    {
+        Self {code, error: None}
+    }
+
+    /// Quits with exit code 1.
+    pub fn failed(error: V39Error) -> Self
+    {
+        Self {code: 1, error: Some(Arc::new(error))}
+    }
+
+    /// Quits with exit code 101, like an uncaught panic.
+    pub(crate) fn panicked(message: String) -> Self
+    {
+        Self {code: 101, error: Some(Arc::new(V39Error::Panic(message)))}
+    }
+
+    pub fn is_success(&self) -> bool
+    {
+        self.code == 0 && self.error.is_none()
+    }
+}
+
+
+impl From<V39Error> for QuitReason
+{
+    fn from(error: V39Error) -> Self
+    {
+        Self::failed(error)
+    }
+}
+
+
+/// How `App::run` ended, `main` can return it to exit with its code.
+#[derive(Clone, Debug)]
+pub struct ExitStatus
+{
+    reason: QuitReason,
+}
+
+
+impl ExitStatus
+{
+    pub(crate) fn new(reason: QuitReason) -> Self
+    {
+        Self {reason}
+    }
+
+    pub fn reason(&self) -> &QuitReason
+    {
+        &self.reason
+    }
+
+    pub fn code(&self) -> i32
+    {
+        self.reason.code
+    }
+
+    pub fn error(&self) -> Option<&V39Error>
+    {
+        self.reason.error.as_deref()
+    }
+
+    pub fn is_success(&self) -> bool
+    {
+        self.reason.is_success()
+    }
+
+    /// Codes outside of 0 to 255 can not be reported on every platform and become 1.
+    pub fn exit_code(&self) -> ExitCode
+    {
+        match (self.reason.code, &self.reason.error)
+        {
+            (0, Some(_)) => ExitCode::FAILURE,
+            (code, _) => ExitCode::from(u8::try_from(code).unwrap_or(1)),
+        }
+    }
+}
+
+
+impl Termination for ExitStatus
+{
+    fn report(self) -> ExitCode
+    {
+        if let Some(error) = self.error()
+        {
+            eprintln!("Error: {}", error.report());
+        }
+
+        self.exit_code()
+    }
+}
+
+
+/// Progress of a shutdown: a quit is requested, receivers may veto it
+/// in `EventReceiver::quit_requested`, otherwise it is accepted and the main loop ends.
+#[derive(Default)]
+pub(crate) struct QuitState
+{
+    requested: Option<QuitReason>,
+    vetoed: bool,
+    accepted: Option<QuitReason>,
+}
+
+
+impl QuitState
+{
+    /// Later requests replace earlier ones, requests after a quit was accepted are ignored.
+    pub(crate) fn request(&mut self, reason: QuitReason)
+    {
+        if self.accepted.is_none()
+        {
+            self.requested = Some(reason);
+        }
+    }
+
+    /// Takes the pending request to ask the receivers about it.
+    pub(crate) fn take_request(&mut self) -> Option<QuitReason>
+    {
+        self.vetoed = false;
+        self.requested.take()
+    }
+
+    pub(crate) fn veto(&mut self)
+    {
+        self.vetoed = true;
+    }
+
+    /// Accepts the request taken last, unless it was vetoed since.
+    pub(crate) fn decide(&mut self, reason: QuitReason) -> bool
+    {
+        if self.vetoed
+        {
+            self.vetoed = false;
+            return false;
+        }
+
+        self.accept(reason);
+        true
+    }
+
+    /// Accepts a quit which can not be vetoed, the first accepted reason is kept.
+    pub(crate) fn accept(&mut self, reason: QuitReason)
+    {
+        if self.accepted.is_none()
+        {
+            self.accepted = Some(reason);
+        }
+
+        self.requested = None;
+    }
+
+    pub(crate) fn accepted(&self) -> Option<&QuitReason>
+    {
+        self.accepted.as_ref()
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn veto_cancels_the_request()
+    {
+        let mut state = QuitState::default();
+
+        state.request(QuitReason::with_code(3));
+        let reason = state.take_request().unwrap();
+        state.veto();
+
+        assert!(!state.decide(reason));
+        assert!(state.accepted().is_none());
+        assert!(state.take_request().is_none());
+
+        // A veto only applies to the request it was given for
+        state.veto();
+        state.request(QuitReason::with_code(4));
+        let reason = state.take_request().unwrap();
+
+        assert!(state.decide(reason));
+        assert_eq!(state.accepted().unwrap().code, 4);
+    }
+
+    #[test]
+    fn first_accepted_reason_is_kept()
+    {
+        let mut state = QuitState::default();
+
+        state.accept(QuitReason::with_code(2));
+        state.accept(QuitReason::success());
+        state.request(QuitReason::with_code(5));
+
+        assert_eq!(state.accepted().unwrap().code, 2);
+        assert!(state.take_request().is_none());
+    }
+
+    #[test]
+    fn exit_codes()
+    {
+        let code = |reason| ExitStatus::new(reason).exit_code();
+
+        assert_eq!(code(QuitReason::success()), ExitCode::SUCCESS);
+        assert_eq!(code(QuitReason::with_code(7)), ExitCode::from(7));
+        assert_eq!(code(QuitReason::with_code(-1)), ExitCode::FAILURE);
+        assert_eq!(code(QuitReason {code: 0, error: Some(Arc::new(V39Error::config("bad")))}), ExitCode::FAILURE);
+        assert!(!ExitStatus::new(QuitReason::failed(V39Error::config("bad"))).is_success());
+    }
+}
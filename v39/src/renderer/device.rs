@@ -21,10 +21,9 @@ pub struct Device
 
 impl Device
 {
-    pub fn init(instance: &Instance, window: &Window, props: &mut VulkanProps) -> V39Result<()>
+    /// Picks a device which can present to the surface of the main window.
+    pub fn init(instance: &Instance, surface: vk::SurfaceKHR, props: &mut VulkanProps) -> V39Result<()>
     {
-        let surface = unsafe {vk_window::create_surface(instance, window, window)}?;
-        
         let mut dev_info = None;
         let mut error_msg = String::new();
         let requirements = DeviceProperties::default();
@@ -38,12 +37,17 @@ impl Device
             }
         }
 
-        if dev_info.is_none() {return Err(V39Error::NoSuitableDevie(error_msg))}
-        let (physical, mut properties) = dev_info.unwrap();
+        let Some((physical, mut properties)) = dev_info else {return Err(V39Error::NoSuitableDevie(error_msg))};
 
-        let stats = properties.stats.take().unwrap();
+        let stats = properties.stats.take()
+            .ok_or_else(|| V39Error::NoSuitableDevie("Device properties were queried without stats".into()))?;
         info!("Found suitable graphics device: {}", stats.props.device_name);
 
+        if let Some(crash) = crate::crash::CrashHandler::get()
+        {
+            crash.set_device(stats.summary());
+        }
+
         let priorities = &[1.0];
         let mut queues = vec![];
         let mut added_indeces: Vec<u32> = vec![];
@@ -138,7 +142,6 @@ impl Device
         };
 
         props.device = Some(Self {physical, properties, stats, logical, graphics, compute, transfer, present});
-        props.surface = Some(surface);
         info!("Vulkan Device Created");
         Ok(())
     }
@@ -151,7 +154,7 @@ impl Device
 
     fn check_device(instance: &Instance, device: vk::PhysicalDevice, requirements: &DeviceProperties, surface: vk::SurfaceKHR) -> V39Result<DeviceProperties>
     {
-        let props = DeviceProperties::from_device(instance, device, surface);
+        let props = DeviceProperties::from_device(instance, device, surface)?;
         props.meets_requirements(requirements)?;
         Ok(props)
     }
@@ -189,16 +192,50 @@ pub struct DeviceStats
 }
 
 
+impl DeviceStats
+{
+    /// Human readable summary, one property per line.
+    pub fn summary(&self) -> String
+    {
+        let version = |v: u32| format!("{}.{}.{}", vk::version_major(v), vk::version_minor(v), vk::version_patch(v));
+
+        let heaps = self.memory.memory_heaps[..self.memory.memory_heap_count as usize]
+            .iter()
+            .map(|heap| format!("{} MiB", heap.size / (1024 * 1024)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "Name: {}\nType: {:?}\nVendor: {:#06x}, Device: {:#06x}\nAPI version: {}\nDriver version: {:#x}\n\
+            Queue families: graphics {:?}, transfer {:?}, compute {:?}, present {:?}\n\
+            Depth format: {:?}\nPresent modes: {:?}\nMemory heaps: {}",
+            self.props.device_name,
+            self.props.device_type,
+            self.props.vendor_id,
+            self.props.device_id,
+            version(self.props.api_version),
+            self.props.driver_version,
+            self.graphics_family_index,
+            self.transfer_family_index,
+            self.compute_family_index,
+            self.present_family_index,
+            self.depth_format,
+            self.present_modes,
+            heaps)
+    }
+}
+
+
 impl DeviceProperties
 {
-    fn from_device(instance: &Instance, device: vk::PhysicalDevice, surface: vk::SurfaceKHR) -> Self
+    fn from_device(instance: &Instance, device: vk::PhysicalDevice, surface: vk::SurfaceKHR) -> V39Result<Self>
     {
         let props = unsafe {instance.get_physical_device_properties(device)};
         let features = unsafe {instance.get_physical_device_features(device)};
         let memory = unsafe {instance.get_physical_device_memory_properties(device)};
-        let capabilities = unsafe {instance.get_physical_device_surface_capabilities_khr(device, surface)}.expect("Unable to obtain Vulkan Surface Capabilities");
-        let formats = unsafe {instance.get_physical_device_surface_formats_khr(device, surface)}.expect("Unable to obtain Vulkan Surface Formats");
-        let present_modes = unsafe {instance.get_physical_device_surface_present_modes_khr(device, surface)}.expect("Unable to obtain Vulkan Surface Present Modes");
+        let capabilities = unsafe {instance.get_physical_device_surface_capabilities_khr(device, surface)}.context("Unable to obtain Vulkan Surface Capabilities")?;
+        let formats = unsafe {instance.get_physical_device_surface_formats_khr(device, surface)}.context("Unable to obtain Vulkan Surface Formats")?;
+        let present_modes = unsafe {instance.get_physical_device_surface_present_modes_khr(device, surface)}.context("Unable to obtain Vulkan Surface Present Modes")?;
      
         info!("Querying device properties of {}", props.device_name);
 
@@ -264,7 +301,7 @@ impl DeviceProperties
                 }
             }
 
-            result.expect("Unknown Depth Format")
+            result.ok_or_else(|| V39Error::NoSuitableDevie("No supported depth format".into()))?
         };
 
         let stats = Some(DeviceStats{
@@ -281,7 +318,7 @@ impl DeviceProperties
             memory,
         });
 
-        Self{graphics, transfer, compute, present, stats, discrete_gpu, sampler_anisontropy, extensions}
+        Ok(Self{graphics, transfer, compute, present, stats, discrete_gpu, sampler_anisontropy, extensions})
     }
 
     fn meets_requirements(&self, req: &Self) -> V39Result<()>
@@ -1,7 +1,7 @@
 use vulkanalia::loader::{LibloadingLoader, LIBRARY};
 use vulkanalia::{vk::ExtDebugUtilsExtension, vk::KhrSwapchainExtension, vk::KhrSurfaceExtension};
 use winit::window::Window;
-use std::collections::HashSet;
+use std::collections::{HashSet, HashMap};
 use once_cell::sync::OnceCell;
 use std::sync::Mutex;
 use std::sync::Arc;
@@ -9,11 +9,14 @@ use std::sync::Arc;
 mod device;
 mod render_prelude;
 mod swapchain;
+mod surface;
 
 pub(crate) mod image;
 pub(crate) mod allocator;
 
 use render_prelude::*;
+use surface::WindowSurface;
+use crate::window::WindowId;
 
 
 pub(crate) const VALIDATION_ENABLED: bool = cfg!(debug_assertions);
@@ -32,6 +35,16 @@ pub(crate) struct Renderer
     instance: Instance,
     entry: Entry,
     window: Arc<Window>,
+    paused: Mutex<HashMap<WindowId, Paused>>,
+}
+
+
+/// Reasons for skipping rendering work, the renderer runs only if none apply.
+#[derive(Copy, Clone, Debug, Default)]
+struct Paused
+{
+    minimized: bool,
+    occluded: bool,
 }
 
 
@@ -39,8 +52,11 @@ impl Renderer
 {
     pub(crate) fn init(window: Arc<Window>) -> V39Result<&'static Self>
     {
-        let loader = unsafe {LibloadingLoader::new(LIBRARY)}.expect("Vulkan Loader Failed");
-        let entry = unsafe {Entry::new(loader)}.expect("Vulkan Entry Failed");
+        let loader = unsafe {LibloadingLoader::new(LIBRARY)}
+            .map_err(|e| V39Error::loader(format!("the Vulkan library {LIBRARY}"), e))?;
+
+        let entry = unsafe {Entry::new(loader)}
+            .map_err(|e| V39Error::loader("the Vulkan entry points", e))?;
 
         let mut debug_info = match VALIDATION_ENABLED
         {
@@ -70,8 +86,11 @@ impl Renderer
         allocator::init_allocator();
         let mut props = VulkanProps::default();
 
-        device::Device::init(&instance, &window, &mut props)?;
-        swapchain::Swapchain::init(&mut props, &window)?;
+        let surface = unsafe {vk_window::create_surface(&instance, &*window, &*window)}?;
+        device::Device::init(&instance, surface, &mut props)?;
+
+        let main = WindowSurface::with_surface(&instance, &mut props, window.clone(), surface)?;
+        props.surfaces.insert(WindowId::MAIN, main);
 
         let sync = Mutex::new(VulkanSync::new(&props)?);
         let props = Mutex::new(props);
@@ -82,6 +101,7 @@ impl Renderer
             instance,
             sync,
             window,
+            paused: Mutex::new(HashMap::new()),
         };
         
         if INSTANCE.set(renderer).is_err()
@@ -93,18 +113,100 @@ impl Renderer
         Ok(INSTANCE.get().unwrap())
     }
 
+    /// Creates a surface and swapchain for a window opened at runtime.
+    pub(crate) fn add_window(&self, id: WindowId, window: Arc<Window>) -> V39Result<()>
+    {
+        let mut props = self.props.lock()
+            .map_err(|_| V39Error::Renderer("Renderer state was poisoned".into()))?;
+
+        let surface = WindowSurface::new(&self.instance, &mut props, window)?;
+        props.surfaces.insert(id, surface);
+
+        info!("Surface for window {id:?} created");
+        Ok(())
+    }
+
+    pub(crate) fn remove_window(&self, id: WindowId)
+    {
+        if let Ok(mut props) = self.props.lock()
+        {
+            if let Some(mut surface) = props.surfaces.remove(&id)
+            {
+                // The swapchain images may still be in use by the GPU
+                if let Ok(device) = props.logical()
+                {
+                    if let Err(e) = unsafe {device.device_wait_idle()}
+                    {
+                        error!("Failed to wait for the device before destroying a surface: {e}");
+                    }
+                }
+
+                surface.destroy_swapchain(&mut props);
+                surface.destroy_surface(&self.instance);
+                info!("Surface for window {id:?} destroyed");
+            }
+        }
+
+        if let Ok(mut paused) = self.paused.lock()
+        {
+            paused.remove(&id);
+        }
+    }
+
+    pub(crate) fn set_minimized(&self, id: WindowId, minimized: bool)
+    {
+        if let Ok(mut paused) = self.paused.lock()
+        {
+            paused.entry(id).or_default().minimized = minimized;
+        }
+    }
+
+    pub(crate) fn set_occluded(&self, id: WindowId, occluded: bool)
+    {
+        if let Ok(mut paused) = self.paused.lock()
+        {
+            paused.entry(id).or_default().occluded = occluded;
+        }
+    }
+
+    /// Nothing is visible while the window is minimized or fully covered,
+    /// so there is no point in recording or presenting frames for it.
+    pub(crate) fn is_paused(&self, id: WindowId) -> bool
+    {
+        let paused = self.paused.lock().unwrap();
+        paused.get(&id).is_some_and(|p| p.minimized || p.occluded)
+    }
+
+    /// Whether every window the renderer presents to is paused.
+    pub(crate) fn all_paused(&self) -> bool
+    {
+        let windows = match self.props.lock()
+        {
+            Ok(props) => props.surfaces.keys().copied().collect::<Vec<_>>(),
+            Err(_) => return false,
+        };
+
+        !windows.is_empty() && windows.into_iter().all(|id| self.is_paused(id))
+    }
+
     pub(crate) fn destroy(&self)
     { 
-        if let Ok(ref mut props) = self.props.lock()
+        // A panic on the main loop thread may have poisoned the state, it is torn down anyway
+        let mut props = self.props.lock().unwrap_or_else(|e| e.into_inner());
+        let sync = self.sync.lock().unwrap_or_else(|e| e.into_inner());
+
+        // The last frame may still be in flight if the main loop ended abruptly
+        if let Ok(device) = props.logical()
         {
-            if let Ok(ref mut sync) = self.sync.lock()
+            if let Err(e) = unsafe {device.device_wait_idle()}
             {
-                sync.destroy(props);
+                error!("Failed to wait for the device before destroying the renderer: {e}");
             }
-
-            props.destroy(&self.instance);
         }
 
+        sync.destroy(&props);
+        props.destroy(&self.instance);
+
         info!("Vulkan Renderer Destroyed");
     }
 }
@@ -114,27 +216,28 @@ impl Renderer
 pub(crate) struct VulkanProps
 {
     pub device: Option<device::Device>,
-    pub surface: Option<vk::SurfaceKHR>,
-    pub swapchain: Option<swapchain::Swapchain>,
+    pub surfaces: HashMap<WindowId, WindowSurface>,
 }
 
 impl VulkanProps
 {
     fn destroy(&mut self, instance: &Instance)
     {
-        if let Some(mut swapchain) = self.swapchain.take()
+        let mut surfaces = std::mem::take(&mut self.surfaces);
+
+        for surface in surfaces.values_mut()
         {
-            swapchain.destroy(self)
-        };
+            surface.destroy_swapchain(self);
+        }
 
         if let Some(ref mut device) = self.device 
         {
             device.destroy();
         }
         
-        if let Some(surface) = self.surface
+        for surface in surfaces.values()
         {
-            unsafe {instance.destroy_surface_khr(surface, alloc())}
+            surface.destroy_surface(instance);
         }
     }
 
@@ -0,0 +1,72 @@
+use crate::renderer::render_prelude::*;
+use crate::renderer::swapchain::Swapchain;
+use vulkanalia::vk::KhrSurfaceExtension;
+use winit::window::Window;
+use std::sync::Arc;
+
+
+/// A window the renderer presents to, with its own surface and swapchain.
+pub(crate) struct WindowSurface
+{
+    pub window: Arc<Window>,
+    pub surface: vk::SurfaceKHR,
+    pub swapchain: Option<Swapchain>,
+}
+
+
+impl WindowSurface
+{
+    pub(crate) fn new(instance: &Instance, vprops: &mut VulkanProps, window: Arc<Window>) -> V39Result<Self>
+    {
+        let surface = unsafe {vk_window::create_surface(instance, &*window, &*window)}?;
+        Self::with_surface(instance, vprops, window, surface)
+    }
+
+    /// Takes ownership of the surface, it is destroyed again if the swapchain can not be created.
+    pub(crate) fn with_surface(instance: &Instance, vprops: &mut VulkanProps, window: Arc<Window>, surface: vk::SurfaceKHR) -> V39Result<Self>
+    {
+        let result = Self::check_support(instance, vprops, surface)
+            .and_then(|_| Swapchain::init(vprops, surface, &window));
+
+        match result
+        {
+            Ok(swapchain) => Ok(Self {window, surface, swapchain: Some(swapchain)}),
+
+            Err(e) => {
+                unsafe {instance.destroy_surface_khr(surface, alloc())};
+                Err(e)
+            },
+        }
+    }
+
+    /// The device was picked for the main window, other windows may live on a display it can not present to.
+    fn check_support(instance: &Instance, vprops: &VulkanProps, surface: vk::SurfaceKHR) -> V39Result<()>
+    {
+        let device = vprops.device()
+            .ok_or_else(|| V39Error::Renderer("Surface was created while the device was uninitialized".into()))?;
+
+        let index = device.stats.present_family_index
+            .ok_or_else(|| V39Error::Renderer("Device has no presentation queue".into()))?;
+
+        if !unsafe {instance.get_physical_device_surface_support_khr(device.physical, index, surface)}?
+        {
+            return Err(V39Error::Renderer("Device can not present to the window".into()));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn destroy_swapchain(&mut self, vprops: &mut VulkanProps)
+    {
+        if let Some(mut swapchain) = self.swapchain.take()
+        {
+            swapchain.destroy(vprops);
+        }
+    }
+
+    /// The swapchain has to be destroyed first.
+    pub(crate) fn destroy_surface(&self, instance: &Instance)
+    {
+        unsafe {instance.destroy_surface_khr(self.surface, alloc())}
+    }
+}
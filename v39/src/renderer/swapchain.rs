@@ -7,6 +7,7 @@ use winit::window::Window;
 
 pub struct Swapchain
 {
+    surface: vk::SurfaceKHR,
     surface_format: vk::SurfaceFormatKHR,
     images: Vec<vk::Image>,
     image_views: Vec<vk::ImageView>,
@@ -20,18 +21,17 @@ pub struct Swapchain
 
 impl Swapchain
 {
-    pub fn init(vprops: &mut VulkanProps, window: &Window) -> V39Result<()>
+    pub fn init(vprops: &mut VulkanProps, surface: vk::SurfaceKHR, window: &Window) -> V39Result<Self>
     {
         let width = window.inner_size().width;
         let height = window.inner_size().height;
 
-        let (surface_format, swapchain) = Self::create(vprops, None, width, height)?;
+        let (surface_format, swapchain) = Self::create(vprops, surface, None, width, height)?;
         let (images, image_views) = Self::get_images(vprops, swapchain, surface_format.format)?;
         let depth_buffer = Self::get_depth_buffer(vprops, width, height)?;
 
-        vprops.swapchain = Some(Swapchain {swapchain, width, height, surface_format, images, image_views, depth_buffer, current_image: 0});
         info!("Vulkan Swapchain Created");
-        Ok(())
+        Ok(Swapchain {surface, swapchain, width, height, surface_format, images, image_views, depth_buffer, current_image: 0})
     }
 
     pub fn recreate(&mut self, vprops: &mut VulkanProps, window: &Window) -> V39Result<()>
@@ -42,7 +42,7 @@ impl Swapchain
         self.width = window.inner_size().width;
         self.height = window.inner_size().height;
 
-        (self.surface_format, self.swapchain) = Self::create(vprops, Some(self.swapchain), self.width, self.height)?;
+        (self.surface_format, self.swapchain) = Self::create(vprops, self.surface, Some(self.swapchain), self.width, self.height)?;
         todo!()
     }
 
@@ -127,7 +127,7 @@ impl Swapchain
         info!("Swapchain Destroyed");
     }
 
-    fn create(vprops: &mut VulkanProps, old_swapchain: Option<vk::SwapchainKHR>, width: u32, height: u32) -> V39Result<(vk::SurfaceFormatKHR, vk::SwapchainKHR)>
+    fn create(vprops: &mut VulkanProps, surface: vk::SurfaceKHR, old_swapchain: Option<vk::SwapchainKHR>, width: u32, height: u32) -> V39Result<(vk::SurfaceFormatKHR, vk::SwapchainKHR)>
     {
         let mut device = &mut vprops.device.as_mut().unwrap();
 
@@ -179,7 +179,7 @@ impl Swapchain
         }
 
         let create_info = vk::SwapchainCreateInfoKHR::builder()
-            .surface(vprops.surface.unwrap())
+            .surface(surface)
             .min_image_count(min_image_count)
             .image_format(format.format)
             .image_color_space(format.color_space)
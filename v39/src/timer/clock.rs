@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+
+/// The clock a receiver's `tick` deltas are taken from.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum Clock
+{
+    /// Wall clock time, keeps running while the game is paused.
+    Real,
+
+    /// Real time scaled by the time scale, stands still while paused.
+    #[default]
+    Game,
+}
+
+
+/// Game time derived from the real frame times.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct GameClock
+{
+    scale: f64,
+    paused: bool,
+    delta: Duration,
+    elapsed: Duration,
+}
+
+
+impl Default for GameClock
+{
+    fn default() -> Self
+    {
+        Self {scale: 1.0, paused: false, delta: Duration::ZERO, elapsed: Duration::ZERO}
+    }
+}
+
+
+impl GameClock
+{
+    pub(crate) fn advance(&mut self, real_delta: Duration)
+    {
+        self.delta = match self.paused
+        {
+            true => Duration::ZERO,
+            false => real_delta.mul_f64(self.scale),
+        };
+
+        self.elapsed += self.delta;
+    }
+
+    pub(crate) fn delta(&self) -> Duration
+    {
+        self.delta
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration
+    {
+        self.elapsed
+    }
+
+    pub(crate) fn scale(&self) -> f64
+    {
+        self.scale
+    }
+
+    /// Negative and non finite scales are clamped to zero, time does not run backwards.
+    pub(crate) fn set_scale(&mut self, scale: f64)
+    {
+        self.scale = match scale.is_finite()
+        {
+            true => scale.max(0.0),
+            false => 0.0,
+        };
+    }
+
+    pub(crate) fn is_paused(&self) -> bool
+    {
+        self.paused
+    }
+
+    pub(crate) fn set_paused(&mut self, paused: bool)
+    {
+        self.paused = paused;
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn scale_stretches_time()
+    {
+        let mut clock = GameClock::default();
+        clock.set_scale(0.5);
+
+        clock.advance(Duration::from_millis(20));
+        clock.advance(Duration::from_millis(20));
+
+        assert_eq!(clock.delta(), Duration::from_millis(10));
+        assert_eq!(clock.elapsed(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn pause_stops_time()
+    {
+        let mut clock = GameClock::default();
+
+        clock.advance(Duration::from_millis(16));
+        clock.set_paused(true);
+        clock.advance(Duration::from_millis(16));
+
+        assert_eq!(clock.delta(), Duration::ZERO);
+        assert_eq!(clock.elapsed(), Duration::from_millis(16));
+    }
+
+    #[test]
+    fn invalid_scales_are_clamped()
+    {
+        let mut clock = GameClock::default();
+
+        clock.set_scale(-2.0);
+        assert_eq!(clock.scale(), 0.0);
+
+        clock.set_scale(f64::NAN);
+        assert_eq!(clock.scale(), 0.0);
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+
+pub(crate) const DEFAULT_FIXED_STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+pub(crate) const DEFAULT_MAX_STEPS: u32 = 8;
+
+
+/// Accumulates frame time and hands it out in steps of equal length.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct FixedStep
+{
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+}
+
+
+impl Default for FixedStep
+{
+    fn default() -> Self
+    {
+        Self::new(DEFAULT_FIXED_STEP, DEFAULT_MAX_STEPS)
+    }
+}
+
+
+impl FixedStep
+{
+    pub(crate) fn new(step: Duration, max_steps: u32) -> Self
+    {
+        Self {step, max_steps, accumulator: Duration::ZERO}
+    }
+
+    pub(crate) fn step(&self) -> Duration
+    {
+        self.step
+    }
+
+    /// Zero steps are ignored, there would be no end to the ticks.
+    pub(crate) fn set_step(&mut self, step: Duration)
+    {
+        if step.is_zero() {return}
+
+        self.step = step;
+        self.accumulator = self.accumulator.min(step);
+    }
+
+    pub(crate) fn max_steps(&self) -> u32
+    {
+        self.max_steps
+    }
+
+    pub(crate) fn set_max_steps(&mut self, max_steps: u32)
+    {
+        self.max_steps = max_steps.max(1);
+    }
+
+    /// Adds the frame time and returns how many steps are due.
+    /// If the simulation falls behind by more than `max_steps`, the backlog is dropped
+    /// instead of making the next frame even longer.
+    pub(crate) fn advance(&mut self, delta: Duration) -> u32
+    {
+        self.accumulator += delta;
+
+        let due = self.accumulator.as_nanos() / self.step.as_nanos();
+        let steps = due.min(self.max_steps as u128) as u32;
+
+        if due > steps as u128
+        {
+            debug!("Fixed step fell behind by {} steps, skipping them", due - steps as u128);
+            self.accumulator = Duration::from_nanos((self.accumulator.as_nanos() % self.step.as_nanos()) as u64);
+        }
+
+        else
+        {
+            self.accumulator -= self.step * steps;
+        }
+
+        steps
+    }
+
+    /// How far the simulation is into the next step, from 0 to 1.
+    pub(crate) fn alpha(&self) -> f32
+    {
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn steps_are_accumulated()
+    {
+        let mut fixed = FixedStep::new(STEP, 8);
+
+        assert_eq!(fixed.advance(Duration::from_millis(4)), 0);
+        assert_eq!(fixed.advance(Duration::from_millis(4)), 0);
+        assert_eq!(fixed.advance(Duration::from_millis(4)), 1);
+        assert_eq!(fixed.advance(Duration::from_millis(25)), 2);
+        assert!((fixed.alpha() - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn backlog_is_dropped_after_max_steps()
+    {
+        let mut fixed = FixedStep::new(STEP, 3);
+
+        assert_eq!(fixed.advance(Duration::from_millis(105)), 3);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-6);
+        assert_eq!(fixed.advance(Duration::from_millis(5)), 1);
+    }
+
+    #[test]
+    fn shorter_step_keeps_alpha_in_range()
+    {
+        let mut fixed = FixedStep::new(STEP, 8);
+
+        fixed.advance(Duration::from_millis(9));
+        fixed.set_step(Duration::from_millis(5));
+
+        assert!(fixed.alpha() <= 1.0);
+        assert_eq!(fixed.advance(Duration::ZERO), 1);
+    }
+}
@@ -0,0 +1,482 @@
+use std::time::Duration;
+use std::sync::{Mutex, Arc};
+use once_cell::sync::OnceCell;
+use crate::prelude::*;
+
+mod fixed;
+pub mod pacing;
+pub mod clock;
+pub mod schedule;
+pub mod stats;
+pub mod source;
+
+pub use clock::Clock;
+use source::{TimeSource, RealClock};
+use schedule::{Scheduler, Schedule, TimerId, TimerStatus};
+use stats::{FrameStats, FrameStatsCollector};
+use clock::GameClock;
+use fixed::FixedStep;
+use pacing::{FramePacing, PacingStats};
+
+
+static INSTANCE: OnceCell<Timer> = OnceCell::new();
+
+
+pub(crate) struct Timer
+{
+    source: Mutex<Arc<dyn TimeSource>>,
+    frame_tracker: Mutex<Tracker>,
+    delta: Mutex<Duration>,
+    game: Mutex<GameClock>,
+    scheduler: Mutex<Scheduler>,
+    frame: Mutex<u64>,
+    pacing: Mutex<FramePacing>,
+    spin_threshold: Mutex<Duration>,
+    frame_stats: Mutex<FrameStatsCollector>,
+    last_pad: Mutex<PadInfo>,
+    fixed: Mutex<FixedStep>,
+}
+
+
+impl Timer
+{
+    pub(crate) fn init() -> V39Result<&'static Self>
+    {
+        let timer = Timer::new(Arc::new(RealClock::default()));
+
+        if INSTANCE.set(timer).is_err()
+        {
+            return Err(V39Error::Reinit("Timer".into()));
+        }
+
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    fn new(source: Arc<dyn TimeSource>) -> Self
+    {
+        Timer {
+            frame_tracker: Mutex::new(Tracker::new(source.now())),
+            source: Mutex::new(source),
+            delta: Mutex::new(Duration::from_secs(0)),
+            game: Mutex::new(GameClock::default()),
+            scheduler: Mutex::new(Scheduler::default()),
+            frame: Mutex::new(0),
+            pacing: Mutex::new(FramePacing::default()),
+            spin_threshold: Mutex::new(pacing::DEFAULT_SPIN_THRESHOLD),
+            frame_stats: Mutex::new(FrameStatsCollector::default()),
+            last_pad: Mutex::new(PadInfo::default()),
+            fixed: Mutex::new(FixedStep::default()),
+        }
+    }
+
+    fn source(&self) -> Arc<dyn TimeSource>
+    {
+        self.source.lock().unwrap().clone()
+    }
+
+    /// Replaces the time source, the running frame is measured from the switch on.
+    pub(crate) fn set_source(&self, source: Arc<dyn TimeSource>)
+    {
+        let now = source.now();
+
+        if let (Ok(mut s), Ok(mut tracker), Ok(mut pad)) = (self.source.lock(), self.frame_tracker.lock(), self.last_pad.lock())
+        {
+            *s = source;
+            *tracker = Tracker::new(now);
+            pad.deadline = None;
+        }
+    }
+
+    pub(crate) fn start_frame_tracker(&self)
+    {
+        let source = self.source();
+
+        if let Ok(mut tracker) = self.frame_tracker.lock()
+        {
+            *tracker = Tracker::new(source.now());
+        }
+
+        source.frame_started();
+    }
+
+    pub(crate) fn end_frame_tracker(&self)
+    {
+        let now = self.source().now();
+
+        if let (Ok(mut tracker), Ok(mut delta)) = (self.frame_tracker.lock(), self.delta.lock())
+        {
+            *delta = tracker.stop(now);
+
+            if let Ok(mut game) = self.game.lock()
+            {
+                game.advance(*delta);
+            }
+
+            if let (Ok(mut stats), Ok(pad)) = (self.frame_stats.lock(), self.last_pad.lock())
+            {
+                stats.record(*delta, pad.work_time, pad.target);
+            }
+        }
+    }
+
+    pub(crate) fn delta_time(&self) -> Duration
+    {
+        *self.delta.lock().expect("Failure isn't an option")
+    }
+
+    pub(crate) fn game_delta_time(&self) -> Duration
+    {
+        self.game.lock().unwrap().delta()
+    }
+
+    pub(crate) fn game_elapsed(&self) -> Duration
+    {
+        self.game.lock().unwrap().elapsed()
+    }
+
+    pub(crate) fn time_scale(&self) -> f64
+    {
+        self.game.lock().unwrap().scale()
+    }
+
+    pub(crate) fn set_time_scale(&self, scale: f64)
+    {
+        if let Ok(mut game) = self.game.lock()
+        {
+            game.set_scale(scale);
+        }
+    }
+
+    pub(crate) fn is_game_paused(&self) -> bool
+    {
+        self.game.lock().unwrap().is_paused()
+    }
+
+    pub(crate) fn set_game_paused(&self, paused: bool)
+    {
+        if let Ok(mut game) = self.game.lock()
+        {
+            game.set_paused(paused);
+        }
+    }
+
+    pub(crate) fn schedule(&self, schedule: Schedule) -> TimerId
+    {
+        self.scheduler.lock().unwrap().schedule(schedule)
+    }
+
+    pub(crate) fn cancel_timer(&self, id: TimerId)
+    {
+        if let Ok(mut scheduler) = self.scheduler.lock()
+        {
+            scheduler.cancel(id);
+        }
+    }
+
+    pub(crate) fn set_timer_paused(&self, id: TimerId, paused: bool)
+    {
+        if let Ok(mut scheduler) = self.scheduler.lock()
+        {
+            scheduler.set_paused(id, paused);
+        }
+    }
+
+    pub(crate) fn reset_timer(&self, id: TimerId)
+    {
+        if let Ok(mut scheduler) = self.scheduler.lock()
+        {
+            scheduler.reset(id);
+        }
+    }
+
+    pub(crate) fn timer_status(&self, id: TimerId) -> Option<TimerStatus>
+    {
+        self.scheduler.lock().unwrap().status(id)
+    }
+
+    /// Advances the scheduled timers by the last game frame time and returns the ones which fired.
+    pub(crate) fn advance_timers(&self) -> Vec<TimerId>
+    {
+        let delta = self.game_delta_time();
+        let paused = self.is_game_paused();
+
+        self.scheduler.lock().unwrap().advance(delta, paused)
+    }
+
+    pub(crate) fn frame(&self) -> u64
+    {
+        *self.frame.lock().unwrap()
+    }
+
+    pub(crate) fn advance_frame(&self)
+    {
+        if let Ok(mut frame) = self.frame.lock()
+        {
+            *frame += 1;
+        }
+    }
+
+    /// Time since the source started, the engine initialization for the real clock.
+    pub(crate) fn elapsed(&self) -> Duration
+    {
+        self.source().now()
+    }
+
+    pub(crate) fn current_frame_time(&self) -> Duration
+    {
+        let now = self.source().now();
+        self.frame_tracker.lock().unwrap().peek(now)
+    }
+
+    pub(crate) fn pacing(&self) -> FramePacing
+    {
+        *self.pacing.lock().unwrap()
+    }
+
+    pub(crate) fn set_pacing(&self, pacing: FramePacing)
+    {
+        if let Ok(mut p) = self.pacing.lock()
+        {
+            *p = pacing;
+        }
+    }
+
+    pub(crate) fn spin_threshold(&self) -> Duration
+    {
+        *self.spin_threshold.lock().unwrap()
+    }
+
+    pub(crate) fn set_spin_threshold(&self, threshold: Duration)
+    {
+        if let Ok(mut t) = self.spin_threshold.lock()
+        {
+            *t = threshold;
+        }
+    }
+
+    pub(crate) fn pacing_stats(&self) -> PacingStats
+    {
+        self.frame_stats.lock().unwrap().pacing_stats()
+    }
+
+    pub(crate) fn frame_stats(&self) -> FrameStats
+    {
+        self.frame_stats.lock().unwrap().stats()
+    }
+
+    pub(crate) fn write_frame_stats_csv(&self, out: &mut impl std::io::Write) -> std::io::Result<()>
+    {
+        self.frame_stats.lock().unwrap().write_csv(out)
+    }
+
+    /// Waits until the current frame took `target`, `None` returns right away.
+    /// Deadlines follow each other `target` apart, so the time between the end of the wait
+    /// and the next frame begin does not add to the frame time.
+    /// After a late frame or a hitch before the frame began they start over instead of catching up.
+    pub(crate) fn pad_frame(&self, target: Option<Duration>)
+    {
+        let source = self.source();
+        let begin = self.frame_tracker.lock().unwrap().begin;
+        let now = source.now();
+
+        let deadline = {
+            let mut last = self.last_pad.lock().unwrap();
+
+            let deadline = target.map(|target| match last.deadline
+            {
+                Some(previous) if previous + target >= begin => previous + target,
+                _ => begin + target,
+            });
+
+            *last = PadInfo {target, work_time: now.saturating_sub(begin), deadline: deadline.map(|d| d.max(now))};
+            deadline
+        };
+
+        if let Some(deadline) = deadline
+        {
+            source.wait_until(deadline, self.spin_threshold());
+        }
+    }
+
+    /// Feeds the last game frame time into the fixed step accumulator and returns the number of due steps.
+    pub(crate) fn advance_fixed_step(&self) -> u32
+    {
+        let delta = self.game_delta_time();
+        self.fixed.lock().unwrap().advance(delta)
+    }
+
+    pub(crate) fn fixed_step(&self) -> Duration
+    {
+        self.fixed.lock().unwrap().step()
+    }
+
+    pub(crate) fn set_fixed_step(&self, step: Duration)
+    {
+        if let Ok(mut fixed) = self.fixed.lock()
+        {
+            fixed.set_step(step);
+        }
+    }
+
+    pub(crate) fn max_fixed_steps(&self) -> u32
+    {
+        self.fixed.lock().unwrap().max_steps()
+    }
+
+    pub(crate) fn set_max_fixed_steps(&self, max_steps: u32)
+    {
+        if let Ok(mut fixed) = self.fixed.lock()
+        {
+            fixed.set_max_steps(max_steps);
+        }
+    }
+
+    pub(crate) fn fixed_alpha(&self) -> f32
+    {
+        self.fixed.lock().unwrap().alpha()
+    }
+}
+
+
+/// What the last frame aimed for, recorded once its length is known.
+#[derive(Copy, Clone, Debug, Default)]
+struct PadInfo
+{
+    target: Option<Duration>,
+    work_time: Duration,
+    deadline: Option<Duration>,
+}
+
+
+/// Times are taken from the timer's source, they only compare to times of the same source.
+#[derive(Copy, Clone, Debug)]
+pub struct Tracker
+{
+    begin: Duration,
+    end: Option<Duration>,
+}
+
+
+impl Tracker
+{
+    fn new(now: Duration) -> Self
+    {
+        Self {
+            begin: now,
+            end: None,
+        }
+    }
+
+    fn stop(&mut self, now: Duration) -> Duration
+    {
+        if self.is_tracking()
+        {
+            self.end = Some(now);
+        }
+
+        let end = self.end.unwrap_or(now);
+        end.saturating_sub(self.begin)
+    }
+
+    fn is_tracking(&self) -> bool
+    {
+        self.end.is_none()
+    }
+
+    fn peek(&self, now: Duration) -> Duration
+    {
+        now.saturating_sub(self.begin)
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use source::ManualClock;
+
+    /// What the frame begin and end receivers and the main loop do with the timer.
+    fn run_frame(timer: &Timer, target: Option<Duration>) -> u32
+    {
+        timer.end_frame_tracker();
+        timer.start_frame_tracker();
+        let fixed_steps = timer.advance_fixed_step();
+
+        timer.pad_frame(target);
+        timer.advance_frame();
+        fixed_steps
+    }
+
+    #[test]
+    fn manual_clock_gives_exact_deltas()
+    {
+        let clock = ManualClock::new(Duration::from_millis(5));
+        let timer = Timer::new(Arc::new(clock.clone()));
+
+        run_frame(&timer, Some(Duration::from_millis(10)));
+
+        for _ in 0..1000
+        {
+            run_frame(&timer, Some(Duration::from_millis(10)));
+            assert_eq!(timer.delta_time(), Duration::from_millis(10));
+        }
+
+        clock.set_step(Duration::from_millis(25));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+
+        assert_eq!(timer.delta_time(), Duration::from_millis(25));
+        assert_eq!(timer.elapsed(), Duration::from_millis(10_060));
+        assert_eq!(timer.frame_stats().over_budget, 1);
+    }
+
+    #[test]
+    fn time_after_padding_does_not_add_to_frames()
+    {
+        let clock = ManualClock::new(Duration::from_millis(5));
+        let timer = Timer::new(Arc::new(clock.clone()));
+
+        for _ in 0..pacing::PACING_WINDOW * 2
+        {
+            run_frame(&timer, Some(Duration::from_millis(10)));
+
+            // Quit checks, the profiler and input run after the pad, before the next frame begins
+            clock.advance(Duration::from_millis(1));
+        }
+
+        assert_eq!(timer.delta_time(), Duration::from_millis(10));
+        assert_eq!(timer.pacing_stats().late_frames, 0);
+        assert_eq!(timer.pacing_stats().mean_frame_time, Duration::from_millis(10));
+
+        // Neither a hitch nor a late frame make the following frames shorter to catch up
+        clock.advance(Duration::from_millis(30));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+        assert_eq!(timer.delta_time(), Duration::from_millis(10));
+
+        clock.set_step(Duration::from_millis(15));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+        clock.set_step(Duration::from_millis(5));
+        clock.advance(Duration::from_millis(1));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+        clock.advance(Duration::from_millis(1));
+        run_frame(&timer, Some(Duration::from_millis(10)));
+        assert_eq!(timer.delta_time(), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn manual_clock_drives_game_time()
+    {
+        let timer = Timer::new(Arc::new(ManualClock::new(Duration::from_millis(10))));
+        timer.set_fixed_step(Duration::from_millis(20));
+        timer.set_time_scale(0.5);
+
+        run_frame(&timer, None);
+
+        let fixed_steps = (0..100).map(|_| run_frame(&timer, None)).sum::<u32>();
+
+        assert_eq!(timer.game_elapsed(), Duration::from_millis(500));
+        assert_eq!(fixed_steps, 25);
+    }
+}
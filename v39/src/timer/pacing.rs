@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+
+/// Most recent frames the pacing statistics cover.
+pub(crate) const PACING_WINDOW: usize = 120;
+
+/// Frames which overshoot the target by more than this count as late.
+const LATE_TOLERANCE: Duration = Duration::from_micros(500);
+
+/// Time before the deadline from which the timer spins instead of sleeping,
+/// sleeping any closer risks oversleeping by a scheduler slice.
+pub(crate) const DEFAULT_SPIN_THRESHOLD: Duration = Duration::from_micros(1500);
+
+
+/// How the timer paces frames.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum FramePacing
+{
+    /// Frames start as soon as the previous one finished.
+    #[default]
+    Unlimited,
+
+    /// Every frame takes at least this long.
+    FrameTime(Duration),
+
+    /// Frames follow the refresh rate of the monitor the main window is on,
+    /// 60 Hz if the platform does not report it.
+    DisplayRefresh,
+}
+
+
+impl FramePacing
+{
+    pub fn from_fps(fps: u64) -> Self
+    {
+        match fps
+        {
+            0 => Self::Unlimited,
+            fps => Self::FrameTime(Duration::from_nanos(1_000_000_000 / fps)),
+        }
+    }
+}
+
+
+/// How closely the recent frames met their target frame time.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct PacingStats
+{
+    /// Number of frames the statistics cover.
+    pub frames: u32,
+
+    /// Target of the last frame, `None` while unlimited.
+    pub target: Option<Duration>,
+
+    pub mean_frame_time: Duration,
+
+    /// Mean distance between frame time and target, frames without a target are skipped.
+    pub mean_error: Duration,
+    pub max_error: Duration,
+
+    /// Frames which took noticeably longer than their target.
+    pub late_frames: u32,
+}
+
+
+impl PacingStats
+{
+    /// Statistics of frame times and their targets, oldest first.
+    pub(crate) fn from_frames(frames: impl Iterator<Item = (Duration, Option<Duration>)>) -> Self
+    {
+        let mut stats = PacingStats::default();
+        let mut targeted = 0;
+        let mut total_error = Duration::ZERO;
+
+        for (frame_time, target) in frames
+        {
+            stats.frames += 1;
+            stats.target = target;
+            stats.mean_frame_time += frame_time;
+
+            if let Some(target) = target
+            {
+                let error = frame_time.abs_diff(target);
+
+                targeted += 1;
+                total_error += error;
+                stats.max_error = stats.max_error.max(error);
+
+                if frame_time > target + LATE_TOLERANCE {stats.late_frames += 1}
+            }
+        }
+
+        if stats.frames == 0 {return stats}
+
+        stats.mean_frame_time /= stats.frames;
+
+        if targeted > 0
+        {
+            stats.mean_error = total_error / targeted;
+        }
+
+        stats
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn fps_is_converted_without_rounding_to_milliseconds()
+    {
+        assert_eq!(FramePacing::from_fps(60), FramePacing::FrameTime(Duration::from_nanos(16_666_666)));
+        assert_eq!(FramePacing::from_fps(0), FramePacing::Unlimited);
+    }
+
+    #[test]
+    fn stats_measure_the_distance_to_the_target()
+    {
+        let target = Some(Duration::from_millis(10));
+
+        let stats = PacingStats::from_frames([
+            (Duration::from_millis(9), target),
+            (Duration::from_millis(12), target),
+            (Duration::from_millis(4), None),
+        ].into_iter());
+
+        assert_eq!(stats.frames, 3);
+        assert_eq!(stats.target, None);
+        assert_eq!(stats.mean_frame_time, Duration::from_nanos(8_333_333));
+        assert_eq!(stats.mean_error, Duration::from_micros(1500));
+        assert_eq!(stats.max_error, Duration::from_millis(2));
+        assert_eq!(stats.late_frames, 1);
+    }
+}
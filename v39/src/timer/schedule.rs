@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+
+/// How often a repeating timer fires within a single frame at most,
+/// after a hitch the missed periods beyond it are dropped.
+pub(crate) const MAX_FIRES_PER_FRAME: u32 = 64;
+
+
+/// Handle to a scheduled timer, see `TimerInterface::schedule`.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, PartialOrd, Ord)]
+pub struct TimerId(u64);
+
+
+/// When a timer fires, durations are measured in game time.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Schedule
+{
+    /// Fires once after the duration.
+    Once(Duration),
+
+    /// Fires every time the duration passed, several times in a long frame but at most 64 times.
+    /// A zero period fires once per frame.
+    Every(Duration),
+
+    /// Fires once after the number of frames in which the game clock was running.
+    Frames(u64),
+}
+
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TimerState
+{
+    Running,
+    Paused,
+
+    /// A one shot timer which fired, it is forgotten at the start of the next frame.
+    Finished,
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TimerStatus
+{
+    pub state: TimerState,
+
+    /// How often the timer fired since it was started or reset.
+    pub times_fired: u32,
+
+    /// Progress towards the next firing from 0 to 1, e.g. for cooldown indicators.
+    pub progress: f32,
+}
+
+
+struct ScheduledTimer
+{
+    schedule: Schedule,
+    elapsed: Duration,
+    frames: u64,
+    paused: bool,
+    finished: bool,
+    times_fired: u32,
+}
+
+
+impl ScheduledTimer
+{
+    fn new(schedule: Schedule) -> Self
+    {
+        Self {schedule, elapsed: Duration::ZERO, frames: 0, paused: false, finished: false, times_fired: 0}
+    }
+
+    /// Returns how often the timer fired.
+    fn advance(&mut self, delta: Duration) -> u32
+    {
+        if self.paused || self.finished {return 0}
+
+        let fired = match self.schedule
+        {
+            Schedule::Once(duration) => {
+                self.elapsed += delta;
+                (self.elapsed >= duration) as u32
+            },
+
+            Schedule::Every(period) if period.is_zero() => 1,
+
+            Schedule::Every(period) => {
+                self.elapsed += delta;
+
+                let due = self.elapsed.as_nanos() / period.as_nanos();
+                let fired = u32::try_from(due).unwrap_or(u32::MAX).min(MAX_FIRES_PER_FRAME);
+
+                if due > fired as u128
+                {
+                    debug!("Timer fell behind by {} periods, skipping them", due - fired as u128);
+                    self.elapsed = Duration::from_nanos((self.elapsed.as_nanos() % period.as_nanos()) as u64);
+                }
+
+                else
+                {
+                    self.elapsed -= period * fired;
+                }
+
+                fired
+            },
+
+            Schedule::Frames(frames) => {
+                self.frames += 1;
+                (self.frames >= frames) as u32
+            },
+        };
+
+        if fired > 0 && !matches!(self.schedule, Schedule::Every(_))
+        {
+            self.finished = true;
+        }
+
+        self.times_fired += fired;
+        fired
+    }
+
+    fn status(&self) -> TimerStatus
+    {
+        let state = match (self.finished, self.paused)
+        {
+            (true, _) => TimerState::Finished,
+            (_, true) => TimerState::Paused,
+            _ => TimerState::Running,
+        };
+
+        let progress = match self.schedule
+        {
+            _ if self.finished => 1.0,
+            Schedule::Once(length) | Schedule::Every(length) if length.is_zero() => 0.0,
+            Schedule::Once(length) | Schedule::Every(length) => (self.elapsed.as_secs_f64() / length.as_secs_f64()) as f32,
+            Schedule::Frames(0) => 0.0,
+            Schedule::Frames(frames) => self.frames as f32 / frames as f32,
+        };
+
+        TimerStatus {state, times_fired: self.times_fired, progress: progress.min(1.0)}
+    }
+}
+
+
+#[derive(Default)]
+pub(crate) struct Scheduler
+{
+    timers: HashMap<TimerId, ScheduledTimer>,
+    next_id: u64,
+}
+
+
+impl Scheduler
+{
+    pub(crate) fn schedule(&mut self, schedule: Schedule) -> TimerId
+    {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+
+        self.timers.insert(id, ScheduledTimer::new(schedule));
+        id
+    }
+
+    pub(crate) fn cancel(&mut self, id: TimerId)
+    {
+        self.timers.remove(&id);
+    }
+
+    pub(crate) fn set_paused(&mut self, id: TimerId, paused: bool)
+    {
+        if let Some(timer) = self.timers.get_mut(&id)
+        {
+            timer.paused = paused;
+        }
+    }
+
+    /// Starts the timer over, keeping it paused if it was.
+    pub(crate) fn reset(&mut self, id: TimerId)
+    {
+        if let Some(timer) = self.timers.get_mut(&id)
+        {
+            *timer = ScheduledTimer {paused: timer.paused, ..ScheduledTimer::new(timer.schedule)};
+        }
+    }
+
+    pub(crate) fn status(&self, id: TimerId) -> Option<TimerStatus>
+    {
+        self.timers.get(&id).map(|timer| timer.status())
+    }
+
+    /// Advances every timer by a frame of game time and returns the timers which fired,
+    /// a repeating timer once for every time it fired.
+    /// Frame timers only count frames in which the game clock is running.
+    pub(crate) fn advance(&mut self, delta: Duration, game_paused: bool) -> Vec<TimerId>
+    {
+        self.timers.retain(|_, timer| !timer.finished);
+
+        if game_paused {return vec![]}
+
+        let mut fired = vec![];
+
+        for (id, timer) in &mut self.timers
+        {
+            for _ in 0..timer.advance(delta)
+            {
+                fired.push(*id);
+            }
+        }
+
+        fired.sort();
+        fired
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn one_shot_fires_once_and_is_forgotten()
+    {
+        let mut scheduler = Scheduler::default();
+        let id = scheduler.schedule(Schedule::Once(Duration::from_millis(25)));
+
+        assert!(scheduler.advance(FRAME, false).is_empty());
+        assert!(scheduler.advance(FRAME, false).is_empty());
+        assert_eq!(scheduler.advance(FRAME, false), vec![id]);
+        assert_eq!(scheduler.status(id).unwrap().state, TimerState::Finished);
+
+        assert!(scheduler.advance(FRAME, false).is_empty());
+        assert_eq!(scheduler.status(id), None);
+    }
+
+    #[test]
+    fn repeating_timer_catches_up_in_long_frames()
+    {
+        let mut scheduler = Scheduler::default();
+        let id = scheduler.schedule(Schedule::Every(Duration::from_millis(4)));
+
+        assert_eq!(scheduler.advance(FRAME, false), vec![id, id]);
+        assert_eq!(scheduler.advance(FRAME, false), vec![id, id, id]);
+        assert_eq!(scheduler.status(id).unwrap().times_fired, 5);
+    }
+
+    #[test]
+    fn repeating_timer_drops_periods_beyond_the_limit()
+    {
+        let mut scheduler = Scheduler::default();
+        let id = scheduler.schedule(Schedule::Every(Duration::from_millis(1)));
+
+        assert_eq!(scheduler.advance(Duration::from_millis(2000) + Duration::from_micros(500), false).len(), MAX_FIRES_PER_FRAME as usize);
+        assert_eq!(scheduler.status(id).unwrap().progress, 0.5);
+        assert_eq!(scheduler.advance(Duration::from_micros(500), false), vec![id]);
+
+        let tiny = scheduler.schedule(Schedule::Every(Duration::from_nanos(1)));
+        assert_eq!(scheduler.advance(Duration::from_secs(10), false).iter().filter(|t| **t == tiny).count(), MAX_FIRES_PER_FRAME as usize);
+        assert_eq!(scheduler.status(tiny).unwrap().progress, 0.0);
+    }
+
+    #[test]
+    fn frame_timer_skips_paused_frames()
+    {
+        let mut scheduler = Scheduler::default();
+        let id = scheduler.schedule(Schedule::Frames(2));
+
+        scheduler.advance(FRAME, false);
+        scheduler.advance(FRAME, true);
+
+        assert_eq!(scheduler.status(id).unwrap().progress, 0.5);
+        assert_eq!(scheduler.advance(FRAME, false), vec![id]);
+    }
+
+    #[test]
+    fn paused_and_reset_timers()
+    {
+        let mut scheduler = Scheduler::default();
+        let id = scheduler.schedule(Schedule::Once(Duration::from_millis(15)));
+
+        scheduler.advance(FRAME, false);
+        scheduler.set_paused(id, true);
+        assert!(scheduler.advance(FRAME, false).is_empty());
+        assert_eq!(scheduler.status(id).unwrap().state, TimerState::Paused);
+
+        scheduler.reset(id);
+        scheduler.set_paused(id, false);
+        assert!(scheduler.advance(FRAME, false).is_empty());
+        assert_eq!(scheduler.advance(FRAME, false), vec![id]);
+
+        scheduler.cancel(id);
+        assert_eq!(scheduler.status(id), None);
+    }
+}
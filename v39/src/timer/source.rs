@@ -0,0 +1,144 @@
+use std::time::{Instant, Duration};
+use std::sync::{Arc, Mutex};
+
+
+/// Where the timer takes the time from.
+/// Times are measured from an arbitrary point, usually the creation of the source.
+pub trait TimeSource: Send + Sync
+{
+    fn now(&self) -> Duration;
+
+    /// Returns once `now` reached the deadline.
+    /// `spin` is how long before the deadline a real clock stops sleeping and spins.
+    fn wait_until(&self, deadline: Duration, spin: Duration);
+
+    /// Called right after a frame started.
+    fn frame_started(&self) {}
+}
+
+
+/// The wall clock, the default source.
+#[derive(Copy, Clone, Debug)]
+pub struct RealClock
+{
+    start: Instant,
+}
+
+
+impl Default for RealClock
+{
+    fn default() -> Self
+    {
+        Self {start: Instant::now()}
+    }
+}
+
+
+impl TimeSource for RealClock
+{
+    fn now(&self) -> Duration
+    {
+        self.start.elapsed()
+    }
+
+    /// Sleeps for most of the time and spins for the rest,
+    /// `thread::sleep` alone tends to overshoot by a millisecond or more.
+    fn wait_until(&self, deadline: Duration, spin: Duration)
+    {
+        let deadline = self.start + deadline;
+
+        loop
+        {
+            let now = Instant::now();
+            if now >= deadline {break}
+
+            let remaining = deadline - now;
+
+            if remaining > spin
+            {
+                std::thread::sleep(remaining - spin);
+            }
+
+            else
+            {
+                std::hint::spin_loop();
+            }
+        }
+    }
+}
+
+
+/// A clock which only moves when told to, for deterministic and headless runs.
+/// Every frame takes exactly `step`, waits for a target frame time return immediately
+/// after jumping to the deadline. Clones share the same time.
+#[derive(Clone, Debug)]
+pub struct ManualClock
+{
+    state: Arc<Mutex<ManualState>>,
+}
+
+
+#[derive(Debug)]
+struct ManualState
+{
+    now: Duration,
+    step: Duration,
+}
+
+
+impl ManualClock
+{
+    pub fn new(step: Duration) -> Self
+    {
+        Self {state: Arc::new(Mutex::new(ManualState {now: Duration::ZERO, step}))}
+    }
+
+    pub fn step(&self) -> Duration
+    {
+        self.state.lock().unwrap().step
+    }
+
+    /// The time every following frame takes, before waiting for the target frame time.
+    pub fn set_step(&self, step: Duration)
+    {
+        if let Ok(mut state) = self.state.lock()
+        {
+            state.step = step;
+        }
+    }
+
+    /// Moves the time forward, e.g. to simulate a hitch.
+    pub fn advance(&self, delta: Duration)
+    {
+        if let Ok(mut state) = self.state.lock()
+        {
+            state.now += delta;
+        }
+    }
+}
+
+
+impl TimeSource for ManualClock
+{
+    fn now(&self) -> Duration
+    {
+        self.state.lock().unwrap().now
+    }
+
+    fn wait_until(&self, deadline: Duration, _spin: Duration)
+    {
+        if let Ok(mut state) = self.state.lock()
+        {
+            state.now = state.now.max(deadline);
+        }
+    }
+
+    fn frame_started(&self)
+    {
+        if let Ok(mut state) = self.state.lock()
+        {
+            let step = state.step;
+            state.now += step;
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::io::Write;
+use super::pacing::{PacingStats, PACING_WINDOW};
+
+
+/// Frames kept for the frame statistics, ten seconds at 60 FPS.
+const STATS_WINDOW: usize = 600;
+
+pub const HISTOGRAM_BUCKET_WIDTH: Duration = Duration::from_millis(2);
+
+/// The last bucket collects every frame which took longer than the others cover.
+pub const HISTOGRAM_BUCKETS: usize = 17;
+
+
+#[derive(Copy, Clone, Debug)]
+struct FrameSample
+{
+    index: u64,
+    frame_time: Duration,
+
+    /// Time spent before waiting for the target frame time.
+    work_time: Duration,
+    target: Option<Duration>,
+}
+
+
+impl FrameSample
+{
+    fn over_budget(&self) -> bool
+    {
+        self.target.is_some_and(|target| self.work_time > target)
+    }
+}
+
+
+/// Summary of the recent frames, see `TimerInterface::frame_stats`.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct FrameStats
+{
+    /// Number of frames the statistics cover.
+    pub frames: u32,
+
+    /// FPS of the last frame.
+    pub current_fps: f32,
+    pub average_fps: f32,
+    pub min_fps: f32,
+    pub max_fps: f32,
+
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+
+    /// Frame counts in buckets of `HISTOGRAM_BUCKET_WIDTH`.
+    pub histogram: [u32; HISTOGRAM_BUCKETS],
+
+    /// Frames whose work took longer than their target frame time, the wait for the target not included.
+    pub over_budget: u32,
+}
+
+
+#[derive(Default)]
+pub(crate) struct FrameStatsCollector
+{
+    samples: VecDeque<FrameSample>,
+    recorded: u64,
+}
+
+
+impl FrameStatsCollector
+{
+    pub(crate) fn record(&mut self, frame_time: Duration, work_time: Duration, target: Option<Duration>)
+    {
+        if self.samples.len() == STATS_WINDOW
+        {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(FrameSample {index: self.recorded, frame_time, work_time, target});
+        self.recorded += 1;
+    }
+
+    pub(crate) fn stats(&self) -> FrameStats
+    {
+        let mut stats = FrameStats {frames: self.samples.len() as u32, ..Default::default()};
+
+        let Some(last) = self.samples.back() else {return stats};
+
+        let mut sorted = self.samples.iter().map(|s| s.frame_time).collect::<Vec<_>>();
+        sorted.sort();
+
+        let total = sorted.iter().sum::<Duration>();
+
+        stats.current_fps = fps(last.frame_time);
+        stats.average_fps = fps(total / stats.frames);
+        stats.min_fps = fps(sorted[sorted.len() - 1]);
+        stats.max_fps = fps(sorted[0]);
+
+        stats.p50 = percentile(&sorted, 50);
+        stats.p95 = percentile(&sorted, 95);
+        stats.p99 = percentile(&sorted, 99);
+
+        for sample in &self.samples
+        {
+            let bucket = (sample.frame_time.as_nanos() / HISTOGRAM_BUCKET_WIDTH.as_nanos()) as usize;
+            stats.histogram[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+
+            if sample.over_budget() {stats.over_budget += 1}
+        }
+
+        stats
+    }
+
+    /// Pacing of the last `PACING_WINDOW` frames.
+    pub(crate) fn pacing_stats(&self) -> PacingStats
+    {
+        let skip = self.samples.len().saturating_sub(PACING_WINDOW);
+        PacingStats::from_frames(self.samples.iter().skip(skip).map(|s| (s.frame_time, s.target)))
+    }
+
+    /// One row per recorded frame, times in milliseconds.
+    pub(crate) fn write_csv(&self, out: &mut impl Write) -> std::io::Result<()>
+    {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        writeln!(out, "frame,frame_time_ms,work_time_ms,target_ms,over_budget")?;
+
+        for sample in &self.samples
+        {
+            let target = sample.target.map(|t| format!("{:.4}", ms(t))).unwrap_or_default();
+
+            writeln!(out, "{},{:.4},{:.4},{},{}",
+                sample.index,
+                ms(sample.frame_time),
+                ms(sample.work_time),
+                target,
+                sample.over_budget() as u8)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+fn fps(frame_time: Duration) -> f32
+{
+    1.0 / frame_time.max(Duration::from_nanos(1)).as_secs_f32()
+}
+
+
+/// Nearest rank percentile of a sorted, non empty slice.
+fn percentile(sorted: &[Duration], p: usize) -> Duration
+{
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ms(ms: u64) -> Duration
+    {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn fps_and_percentiles()
+    {
+        let mut collector = FrameStatsCollector::default();
+
+        for i in 1..=100
+        {
+            collector.record(ms(i), ms(i), None);
+        }
+
+        let stats = collector.stats();
+
+        assert_eq!(stats.frames, 100);
+        assert!((stats.current_fps - 10.0).abs() < 1e-3);
+        assert!((stats.max_fps - 1000.0).abs() < 1e-1);
+        assert!((stats.min_fps - 10.0).abs() < 1e-3);
+        assert_eq!(stats.p50, ms(50));
+        assert_eq!(stats.p95, ms(95));
+        assert_eq!(stats.p99, ms(99));
+        assert_eq!(stats.histogram[0], 1);
+        assert_eq!(stats.histogram[HISTOGRAM_BUCKETS - 1], 69);
+    }
+
+    #[test]
+    fn budget_ignores_the_wait_for_the_target()
+    {
+        let mut collector = FrameStatsCollector::default();
+
+        collector.record(ms(17), ms(5), Some(ms(16)));
+        collector.record(ms(20), ms(20), Some(ms(16)));
+        collector.record(ms(20), ms(20), None);
+
+        assert_eq!(collector.stats().over_budget, 1);
+    }
+
+    #[test]
+    fn pacing_covers_only_recent_frames()
+    {
+        let mut collector = FrameStatsCollector::default();
+
+        collector.record(Duration::from_secs(1), ms(1), Some(ms(10)));
+
+        for _ in 0..PACING_WINDOW
+        {
+            collector.record(ms(10), ms(5), Some(ms(10)));
+        }
+
+        let pacing = collector.pacing_stats();
+
+        assert_eq!(pacing.frames, PACING_WINDOW as u32);
+        assert_eq!(pacing.mean_frame_time, ms(10));
+        assert_eq!(pacing.late_frames, 0);
+        assert_eq!(collector.stats().frames, PACING_WINDOW as u32 + 1);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_frame()
+    {
+        let mut collector = FrameStatsCollector::default();
+        collector.record(ms(20), ms(18), Some(ms(16)));
+        collector.record(ms(4), ms(4), None);
+
+        let mut out = vec![];
+        collector.write_csv(&mut out).unwrap();
+
+        let csv = String::from_utf8(out).unwrap();
+        let rows = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(rows, [
+            "frame,frame_time_ms,work_time_ms,target_ms,over_budget",
+            "0,20.0000,18.0000,16.0000,1",
+            "1,4.0000,4.0000,,0",
+        ]);
+    }
+}
@@ -0,0 +1,204 @@
+use std::f32::consts::PI;
+
+
+/// Maps the linear progress of a tween from 0 to 1 onto a curve.
+/// Elastic curves overshoot, they leave the 0 to 1 range in between.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub enum Easing
+{
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+
+    /// A CSS style cubic bezier through (0, 0), (x1, y1), (x2, y2) and (1, 1).
+    /// The x coordinates are clamped to 0 to 1, so the curve stays a function of time.
+    CubicBezier(f32, f32, f32, f32),
+}
+
+
+impl Easing
+{
+    pub fn apply(&self, t: f32) -> f32
+    {
+        let t = t.clamp(0.0, 1.0);
+
+        match *self
+        {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut => match t < 0.5
+            {
+                true => 2.0 * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            },
+
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => match t < 0.5
+            {
+                true => 4.0 * t.powi(3),
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            },
+
+            Easing::ElasticIn => elastic_in(t),
+            Easing::ElasticOut => 1.0 - elastic_in(1.0 - t),
+            Easing::ElasticInOut => match t < 0.5
+            {
+                true => elastic_in(2.0 * t) / 2.0,
+                false => 1.0 - elastic_in(2.0 - 2.0 * t) / 2.0,
+            },
+
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => match t < 0.5
+            {
+                true => (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0,
+                false => (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0,
+            },
+
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(t, x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2),
+        }
+    }
+}
+
+
+fn elastic_in(t: f32) -> f32
+{
+    if t <= 0.0 || t >= 1.0 {return t}
+
+    let c4 = 2.0 * PI / 3.0;
+    -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * c4).sin()
+}
+
+
+fn bounce_out(t: f32) -> f32
+{
+    let n1 = 7.5625;
+    let d1 = 2.75;
+
+    if t < 1.0 / d1
+    {
+        n1 * t * t
+    }
+
+    else if t < 2.0 / d1
+    {
+        let t = t - 1.5 / d1;
+        n1 * t * t + 0.75
+    }
+
+    else if t < 2.5 / d1
+    {
+        let t = t - 2.25 / d1;
+        n1 * t * t + 0.9375
+    }
+
+    else
+    {
+        let t = t - 2.625 / d1;
+        n1 * t * t + 0.984375
+    }
+}
+
+
+/// Finds the curve parameter whose x is `t` and returns its y.
+fn cubic_bezier(t: f32, x1: f32, y1: f32, x2: f32, y2: f32) -> f32
+{
+    let bezier = |s: f32, p1: f32, p2: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+    };
+
+    let slope = |s: f32, p1: f32, p2: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * p1 + 6.0 * inv * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+    };
+
+    // Newton's method converges in a few steps unless the curve is flat,
+    // bisection catches the rest since x grows monotonically
+    let mut s = t;
+
+    for _ in 0..8
+    {
+        let dx = bezier(s, x1, x2) - t;
+        if dx.abs() < 1e-6 {return bezier(s, y1, y2)}
+
+        let d = slope(s, x1, x2);
+        if d.abs() < 1e-6 {break}
+
+        s = (s - dx / d).clamp(0.0, 1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    s = t;
+
+    for _ in 0..32
+    {
+        let x = bezier(s, x1, x2);
+        if (x - t).abs() < 1e-6 {break}
+
+        if x < t {low = s} else {high = s}
+        s = (low + high) / 2.0;
+    }
+
+    bezier(s, y1, y2)
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const ALL: [Easing; 14] = [
+        Easing::Linear, Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+        Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut,
+        Easing::ElasticIn, Easing::ElasticOut, Easing::ElasticInOut,
+        Easing::BounceIn, Easing::BounceOut, Easing::BounceInOut,
+        Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+    ];
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one()
+    {
+        for easing in ALL
+        {
+            assert!(easing.apply(0.0).abs() < 1e-4, "{easing:?}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-4, "{easing:?}");
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric()
+    {
+        for easing in [Easing::QuadInOut, Easing::CubicInOut, Easing::ElasticInOut, Easing::BounceInOut]
+        {
+            assert!((easing.apply(0.5) - 0.5).abs() < 1e-4, "{easing:?}");
+            assert!((easing.apply(0.2) + easing.apply(0.8) - 1.0).abs() < 1e-4, "{easing:?}");
+        }
+    }
+
+    #[test]
+    fn linear_bezier_is_linear()
+    {
+        let easing = Easing::CubicBezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0);
+
+        for i in 0..=10
+        {
+            let t = i as f32 / 10.0;
+            assert!((easing.apply(t) - t).abs() < 1e-4);
+        }
+    }
+}
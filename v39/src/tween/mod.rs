@@ -0,0 +1,429 @@
+pub mod easing;
+pub use easing::Easing;
+
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::prelude::*;
+
+
+static INSTANCE: OnceCell<TweenManager> = OnceCell::new();
+
+/// Channel of tweens which were not given one.
+pub const DEFAULT_CHANNEL: &str = "value";
+
+
+/// Handle to a playing animation, see `TweenInterface::play`.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, PartialOrd, Ord)]
+pub struct TweenId(u64);
+
+
+/// Interpolates a value on a channel from `from` to `to`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Tween
+{
+    from: f32,
+    to: f32,
+    duration: Duration,
+    easing: Easing,
+    channel: String,
+}
+
+
+impl Tween
+{
+    pub fn new(from: f32, to: f32, duration: Duration) -> Self
+    {
+        Self {from, to, duration, easing: Easing::Linear, channel: DEFAULT_CHANNEL.into()}
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self
+    {
+        self.easing = easing;
+        self
+    }
+
+    /// The name the value is read back with, tweens in sequences and groups
+    /// can animate different channels, e.g. "x" and "zoom".
+    pub fn channel(mut self, channel: impl Into<String>) -> Self
+    {
+        self.channel = channel.into();
+        self
+    }
+
+    fn value_at(&self, t: Duration) -> f32
+    {
+        let progress = match self.duration.is_zero()
+        {
+            true => 1.0,
+            false => (t.as_secs_f64() / self.duration.as_secs_f64()) as f32,
+        };
+
+        self.from + (self.to - self.from) * self.easing.apply(progress)
+    }
+}
+
+
+/// Tweens combined into a timeline.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Animation
+{
+    Tween(Tween),
+
+    /// Waits without changing any channel.
+    Delay(Duration),
+
+    /// Plays one animation after the other.
+    Sequence(Vec<Animation>),
+
+    /// Plays all animations at once, the group lasts as long as the longest one.
+    Group(Vec<Animation>),
+}
+
+
+impl From<Tween> for Animation
+{
+    fn from(tween: Tween) -> Self
+    {
+        Animation::Tween(tween)
+    }
+}
+
+
+impl Animation
+{
+    pub fn duration(&self) -> Duration
+    {
+        match self
+        {
+            Animation::Tween(tween) => tween.duration,
+            Animation::Delay(delay) => *delay,
+            Animation::Sequence(animations) => animations.iter().map(|a| a.duration()).sum(),
+            Animation::Group(animations) => animations.iter().map(|a| a.duration()).max().unwrap_or_default(),
+        }
+    }
+
+    fn has_channel(&self, channel: &str) -> bool
+    {
+        match self
+        {
+            Animation::Tween(tween) => tween.channel == channel,
+            Animation::Delay(_) => false,
+            Animation::Sequence(animations) | Animation::Group(animations) => animations.iter().any(|a| a.has_channel(channel)),
+        }
+    }
+
+    /// The value of the channel at `t`.
+    /// Before its first tween started a channel holds that tween's start value,
+    /// between and after tweens it holds the end value of the last one.
+    fn sample(&self, t: Duration, channel: &str) -> Option<f32>
+    {
+        match self
+        {
+            Animation::Tween(tween) if tween.channel == channel => Some(tween.value_at(t)),
+            Animation::Tween(_) | Animation::Delay(_) => None,
+
+            Animation::Sequence(animations) => {
+                let mut value = None;
+                let mut offset = Duration::ZERO;
+
+                for animation in animations
+                {
+                    if animation.has_channel(channel)
+                    {
+                        if t < offset
+                        {
+                            return value.or_else(|| animation.sample(Duration::ZERO, channel));
+                        }
+
+                        value = animation.sample(t - offset, channel);
+                    }
+
+                    offset += animation.duration();
+                }
+
+                value
+            },
+
+            // Later animations win if several animate the same channel
+            Animation::Group(animations) => animations.iter().rev().find_map(|a| a.sample(t, channel)),
+        }
+    }
+}
+
+
+/// How often an animation plays.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum LoopMode
+{
+    #[default]
+    Once,
+
+    /// Starts over from the beginning, for the number of cycles or forever.
+    Loop(Option<u32>),
+
+    /// Plays forwards and backwards in turns, every direction counts as a cycle.
+    PingPong(Option<u32>),
+}
+
+
+impl LoopMode
+{
+    fn cycles(&self) -> Option<u32>
+    {
+        match *self
+        {
+            LoopMode::Once => Some(1),
+            LoopMode::Loop(cycles) | LoopMode::PingPong(cycles) => cycles.map(|c| c.max(1)),
+        }
+    }
+}
+
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TweenState
+{
+    Playing,
+    Paused,
+
+    /// Holds the final values, it is forgotten at the start of the next frame.
+    Finished,
+}
+
+
+struct Playing
+{
+    animation: Animation,
+    mode: LoopMode,
+    elapsed: Duration,
+    paused: bool,
+    finished: bool,
+}
+
+
+impl Playing
+{
+    /// Time on the animation's own timeline, with loops and ping pong folded in.
+    fn local_time(&self) -> Duration
+    {
+        let length = self.animation.duration();
+        if length.is_zero() {return length}
+
+        let mut cycle = (self.elapsed.as_nanos() / length.as_nanos()) as u32;
+        let mut t = Duration::from_nanos((self.elapsed.as_nanos() % length.as_nanos()) as u64);
+
+        // A finished animation shows the end of its last cycle, not the start of the next one
+        if t.is_zero() && cycle > 0 && self.finished
+        {
+            cycle -= 1;
+            t = length;
+        }
+
+        match self.mode
+        {
+            LoopMode::PingPong(_) if cycle % 2 == 1 => length - t,
+            _ => t,
+        }
+    }
+
+    fn advance(&mut self, delta: Duration) -> bool
+    {
+        if self.paused || self.finished {return false}
+
+        self.elapsed += delta;
+
+        if let Some(cycles) = self.mode.cycles()
+        {
+            let end = self.animation.duration() * cycles;
+
+            if self.elapsed >= end
+            {
+                self.elapsed = end;
+                self.finished = true;
+            }
+        }
+
+        self.finished
+    }
+}
+
+
+#[derive(Default)]
+pub(crate) struct Tweens
+{
+    playing: HashMap<TweenId, Playing>,
+    next_id: u64,
+}
+
+
+impl Tweens
+{
+    pub(crate) fn play(&mut self, animation: Animation, mode: LoopMode) -> TweenId
+    {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+
+        self.playing.insert(id, Playing {animation, mode, elapsed: Duration::ZERO, paused: false, finished: false});
+        id
+    }
+
+    pub(crate) fn stop(&mut self, id: TweenId)
+    {
+        self.playing.remove(&id);
+    }
+
+    pub(crate) fn set_paused(&mut self, id: TweenId, paused: bool)
+    {
+        if let Some(playing) = self.playing.get_mut(&id)
+        {
+            playing.paused = paused;
+        }
+    }
+
+    pub(crate) fn value(&self, id: TweenId, channel: &str) -> Option<f32>
+    {
+        let playing = self.playing.get(&id)?;
+        playing.animation.sample(playing.local_time(), channel)
+    }
+
+    pub(crate) fn state(&self, id: TweenId) -> Option<TweenState>
+    {
+        self.playing.get(&id).map(|playing| match (playing.finished, playing.paused)
+        {
+            (true, _) => TweenState::Finished,
+            (_, true) => TweenState::Paused,
+            _ => TweenState::Playing,
+        })
+    }
+
+    /// Advances every animation and returns the ones which finished.
+    pub(crate) fn advance(&mut self, delta: Duration) -> Vec<TweenId>
+    {
+        self.playing.retain(|_, playing| !playing.finished);
+
+        let mut finished = self.playing.iter_mut()
+            .filter_map(|(id, playing)| playing.advance(delta).then_some(*id))
+            .collect::<Vec<_>>();
+
+        finished.sort();
+        finished
+    }
+}
+
+
+pub(crate) struct TweenManager
+{
+    tweens: Mutex<Tweens>,
+}
+
+
+impl TweenManager
+{
+    pub(crate) fn init() -> V39Result<&'static Self>
+    {
+        let manager = TweenManager {tweens: Mutex::new(Tweens::default())};
+
+        if INSTANCE.set(manager).is_err()
+        {
+            return Err(V39Error::Reinit("Tween Manager".into()));
+        }
+
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    pub(crate) fn tweens(&self) -> std::sync::MutexGuard<'_, Tweens>
+    {
+        self.tweens.lock().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ms(ms: u64) -> Duration
+    {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn sequence_holds_values_between_tweens()
+    {
+        let animation = Animation::Sequence(vec![
+            Tween::new(0.0, 10.0, ms(100)).into(),
+            Animation::Delay(ms(100)),
+            Tween::new(10.0, 0.0, ms(100)).into(),
+        ]);
+
+        assert_eq!(animation.duration(), ms(300));
+        assert_eq!(animation.sample(ms(50), DEFAULT_CHANNEL), Some(5.0));
+        assert_eq!(animation.sample(ms(150), DEFAULT_CHANNEL), Some(10.0));
+        assert_eq!(animation.sample(ms(250), DEFAULT_CHANNEL), Some(5.0));
+        assert_eq!(animation.sample(ms(400), DEFAULT_CHANNEL), Some(0.0));
+    }
+
+    #[test]
+    fn channels_wait_for_their_first_tween()
+    {
+        let animation = Animation::Sequence(vec![
+            Tween::new(0.0, 1.0, ms(100)).channel("x").into(),
+            Tween::new(2.0, 4.0, ms(100)).channel("zoom").into(),
+        ]);
+
+        assert_eq!(animation.sample(ms(50), "zoom"), Some(2.0));
+        assert_eq!(animation.sample(ms(150), "zoom"), Some(3.0));
+        assert_eq!(animation.sample(ms(150), "x"), Some(1.0));
+        assert_eq!(animation.sample(ms(150), "y"), None);
+    }
+
+    #[test]
+    fn group_plays_in_parallel()
+    {
+        let animation = Animation::Group(vec![
+            Tween::new(0.0, 1.0, ms(100)).channel("x").into(),
+            Tween::new(0.0, 1.0, ms(200)).channel("y").into(),
+        ]);
+
+        assert_eq!(animation.duration(), ms(200));
+        assert_eq!(animation.sample(ms(100), "x"), Some(1.0));
+        assert_eq!(animation.sample(ms(100), "y"), Some(0.5));
+    }
+
+    #[test]
+    fn ping_pong_reverses_and_finishes()
+    {
+        let mut tweens = Tweens::default();
+        let id = tweens.play(Tween::new(0.0, 10.0, ms(100)).into(), LoopMode::PingPong(Some(2)));
+
+        tweens.advance(ms(100));
+        assert_eq!(tweens.value(id, DEFAULT_CHANNEL), Some(10.0));
+
+        tweens.advance(ms(25));
+        assert_eq!(tweens.value(id, DEFAULT_CHANNEL), Some(7.5));
+
+        assert_eq!(tweens.advance(ms(100)), vec![id]);
+        assert_eq!(tweens.state(id), Some(TweenState::Finished));
+        assert_eq!(tweens.value(id, DEFAULT_CHANNEL), Some(0.0));
+
+        tweens.advance(ms(10));
+        assert_eq!(tweens.state(id), None);
+    }
+
+    #[test]
+    fn loops_forever()
+    {
+        let mut tweens = Tweens::default();
+        let id = tweens.play(Tween::new(0.0, 10.0, ms(100)).into(), LoopMode::Loop(None));
+
+        for _ in 0..100
+        {
+            assert!(tweens.advance(ms(30)).is_empty());
+        }
+
+        assert_eq!(tweens.value(id, DEFAULT_CHANNEL), Some(0.0));
+        assert_eq!(tweens.state(id), Some(TweenState::Playing));
+    }
+}
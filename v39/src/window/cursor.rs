@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+
+/// How the cursor is bound to the window.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum CursorGrab
+{
+    /// The cursor moves freely.
+    #[default]
+    None,
+
+    /// The cursor can not leave the window.
+    Confined,
+
+    /// The cursor stays in place, movement is only reported as raw mouse deltas.
+    /// This is what first person cameras want, see `InputManagerInterface::mouse_delta`.
+    Locked,
+}
+
+
+/// The system cursors available on every platform.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum CursorIcon
+{
+    #[default]
+    Default,
+    Pointer,
+    Text,
+    Crosshair,
+    Move,
+    Grab,
+    Grabbing,
+    NotAllowed,
+    Wait,
+    ResizeHorizontal,
+    ResizeVertical,
+}
@@ -0,0 +1,33 @@
+use crate::prelude::*;
+use winit::window::Icon;
+
+
+/// Window icon in RGBA8, rows from top to bottom.
+#[derive(Clone, Debug)]
+pub struct WindowIcon
+{
+    icon: Icon,
+    size: (u32, u32),
+}
+
+
+impl WindowIcon
+{
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> V39Result<Self>
+    {
+        let icon = Icon::from_rgba(rgba, width, height)
+            .map_err(|e| V39Error::InvalidWindowIcon(e.to_string()))?;
+
+        Ok(Self {icon, size: (width, height)})
+    }
+
+    pub fn size(&self) -> (u32, u32)
+    {
+        self.size
+    }
+
+    pub(crate) fn to_winit(&self) -> Icon
+    {
+        self.icon.clone()
+    }
+}
@@ -0,0 +1,610 @@
+pub mod cursor;
+pub mod icon;
+pub use cursor::{CursorIcon, CursorGrab};
+pub use icon::WindowIcon;
+
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::sync::{Mutex, Arc};
+use crate::prelude::*;
+
+use winit::{
+    window::Window,
+    window::WindowBuilder,
+    window::CursorGrabMode,
+    window::WindowLevel,
+    event_loop::{EventLoopProxy, EventLoopWindowTarget},
+    dpi::{PhysicalPosition, PhysicalSize},
+};
+
+
+static INSTANCE: OnceCell<WindowManager> = OnceCell::new();
+
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum Fullscreen
+{
+    #[default]
+    Windowed,
+
+    /// A borderless window covering the monitor the window is on.
+    Borderless,
+
+    /// Takes over the monitor the window is on with its largest video mode.
+    Exclusive,
+}
+
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Theme
+{
+    Light,
+    Dark,
+}
+
+
+impl Theme
+{
+    pub(crate) fn from_winit(theme: winit::window::Theme) -> Self
+    {
+        match theme
+        {
+            winit::window::Theme::Light => Theme::Light,
+            winit::window::Theme::Dark => Theme::Dark,
+        }
+    }
+}
+
+
+/// Identifies a window of the app, ids are never reused.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, PartialOrd, Ord)]
+pub struct WindowId(u64);
+
+
+impl WindowId
+{
+    /// The window created by `v39::init`.
+    pub const MAIN: WindowId = WindowId(0);
+}
+
+
+/// Initial settings of a window opened at runtime, see `App::open_window`.
+#[derive(Clone, Debug)]
+pub struct WindowSettings
+{
+    title: String,
+    size: Option<(u32, u32)>,
+}
+
+
+impl WindowSettings
+{
+    pub fn new(title: impl Into<String>) -> Self
+    {
+        Self {title: title.into(), size: None}
+    }
+
+    /// Inner size in physical pixels, the platform picks one if unset.
+    pub fn size(mut self, size: (u32, u32)) -> Self
+    {
+        self.size = Some(size);
+        self
+    }
+}
+
+
+/// A change to a window, applied on the event loop thread.
+#[derive(Clone, Debug)]
+pub(crate) enum WindowRequest
+{
+    Open(WindowSettings),
+    Close,
+    Title(String),
+    Size((u32, u32)),
+    MinSize(Option<(u32, u32)>),
+    MaxSize(Option<(u32, u32)>),
+    Fullscreen(Fullscreen),
+    Maximized(bool),
+    Minimized(bool),
+    Decorations(bool),
+    AlwaysOnTop(bool),
+    Icon(Option<WindowIcon>),
+    CursorVisible(bool),
+    CursorGrab(CursorGrab),
+    CursorPosition((f64, f64)),
+    Cursor(CursorIcon),
+
+    /// Does nothing but wake the event loop, e.g. to let it see an accepted quit.
+    Wake,
+}
+
+
+/// What the event loop sends to itself through its proxy.
+#[derive(Clone, Debug)]
+pub(crate) struct WindowMessage
+{
+    pub(crate) id: WindowId,
+    pub(crate) request: WindowRequest,
+}
+
+
+/// Windows opened or closed by applying a request.
+pub(crate) enum WindowChange
+{
+    Opened(WindowId, Arc<Window>),
+    Closed(WindowId),
+}
+
+
+/// The window as last requested, so queries do not have to wait for the event loop.
+#[derive(Clone, Debug, Default)]
+struct WindowState
+{
+    title: String,
+    size: (u32, u32),
+    min_size: Option<(u32, u32)>,
+    max_size: Option<(u32, u32)>,
+    fullscreen: Fullscreen,
+    maximized: bool,
+    minimized: bool,
+    decorations: bool,
+    always_on_top: bool,
+    cursor_visible: bool,
+    cursor_grab: CursorGrab,
+    cursor: CursorIcon,
+
+    /// Refresh rate of the monitor the window is on in millihertz.
+    refresh_rate: Option<u32>,
+}
+
+
+impl WindowState
+{
+    fn from_window(window: &Window) -> Self
+    {
+        let size = window.inner_size();
+
+        Self {
+            title: window.title(),
+            size: (size.width, size.height),
+            maximized: window.is_maximized(),
+            decorations: window.is_decorated(),
+            cursor_visible: true,
+            refresh_rate: refresh_rate(window),
+            ..Default::default()
+        }
+    }
+}
+
+
+/// A window which is `None` until the event loop got around to creating it.
+struct ManagedWindow
+{
+    window: Option<Arc<Window>>,
+    state: WindowState,
+}
+
+
+pub(crate) struct WindowManager
+{
+    windows: Mutex<HashMap<WindowId, ManagedWindow>>,
+    proxy: Mutex<EventLoopProxy<WindowMessage>>,
+    next_id: Mutex<u64>,
+}
+
+
+impl WindowManager
+{
+    pub(crate) fn init(window: Arc<Window>, proxy: EventLoopProxy<WindowMessage>) -> V39Result<&'static Self>
+    {
+        let main = ManagedWindow {
+            state: WindowState::from_window(&window),
+            window: Some(window),
+        };
+
+        let manager = WindowManager {
+            windows: Mutex::new(HashMap::from([(WindowId::MAIN, main)])),
+            proxy: Mutex::new(proxy),
+            next_id: Mutex::new(1),
+        };
+
+        if INSTANCE.set(manager).is_err()
+        {
+            return Err(V39Error::Reinit("Window Manager".into()));
+        }
+
+        Ok(INSTANCE.get().unwrap())
+    }
+
+    /// Reserves an id and asks the event loop to create the window.
+    /// Requests for the id made before the window exists are applied right after it was created.
+    pub(crate) fn open(&self, settings: WindowSettings) -> WindowId
+    {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            WindowId(*next_id - 1)
+        };
+
+        let state = WindowState {
+            title: settings.title.clone(),
+            size: settings.size.unwrap_or_default(),
+            decorations: true,
+            cursor_visible: true,
+            ..Default::default()
+        };
+
+        if let Ok(mut windows) = self.windows.lock()
+        {
+            windows.insert(id, ManagedWindow {window: None, state});
+        }
+
+        self.request(id, WindowRequest::Open(settings));
+        id
+    }
+
+    /// Hands the request to the event loop thread, some platforms only allow
+    /// window changes from the thread that created the window.
+    pub(crate) fn request(&self, id: WindowId, request: WindowRequest)
+    {
+        self.update_state(id, |state| match &request
+        {
+            WindowRequest::Title(title) => state.title = title.clone(),
+            WindowRequest::MinSize(size) => state.min_size = *size,
+            WindowRequest::MaxSize(size) => state.max_size = *size,
+            WindowRequest::Fullscreen(fullscreen) => state.fullscreen = *fullscreen,
+            WindowRequest::Maximized(maximized) => state.maximized = *maximized,
+            WindowRequest::Minimized(minimized) => state.minimized = *minimized,
+            WindowRequest::Decorations(decorations) => state.decorations = *decorations,
+            WindowRequest::AlwaysOnTop(on_top) => state.always_on_top = *on_top,
+            WindowRequest::CursorVisible(visible) => state.cursor_visible = *visible,
+            WindowRequest::CursorGrab(grab) => state.cursor_grab = *grab,
+            WindowRequest::Cursor(icon) => state.cursor = *icon,
+
+            // The size is tracked through resize events, icons and cursor positions are not queried
+            WindowRequest::Size(_) | WindowRequest::Icon(_) | WindowRequest::CursorPosition(_) => {},
+            WindowRequest::Open(_) | WindowRequest::Close | WindowRequest::Wake => {},
+        });
+
+        if let Ok(proxy) = self.proxy.lock()
+        {
+            if proxy.send_event(WindowMessage {id, request}).is_err()
+            {
+                warn!("Window request dropped, the event loop is closed");
+            }
+        }
+    }
+
+    fn update_state(&self, id: WindowId, f: impl FnOnce(&mut WindowState))
+    {
+        if let Ok(mut windows) = self.windows.lock()
+        {
+            if let Some(managed) = windows.get_mut(&id)
+            {
+                f(&mut managed.state);
+            }
+        }
+    }
+
+    /// Reads the state of the window, the default state for windows which are closed.
+    fn read_state<T: Default>(&self, id: WindowId, f: impl FnOnce(&WindowState) -> T) -> T
+    {
+        self.windows.lock().unwrap()
+            .get(&id)
+            .map(|managed| f(&managed.state))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_size(&self, id: WindowId, size: (u32, u32))
+    {
+        self.update_state(id, |state| state.size = size);
+    }
+
+    pub(crate) fn set_minimized(&self, id: WindowId, minimized: bool)
+    {
+        self.update_state(id, |state| state.minimized = minimized);
+    }
+
+    /// Queries the monitor again, the window may have moved to another one.
+    /// Must only be called on the event loop thread.
+    pub(crate) fn update_monitor(&self, id: WindowId)
+    {
+        if let Ok(mut windows) = self.windows.lock()
+        {
+            if let Some(ManagedWindow {window: Some(window), state}) = windows.get_mut(&id)
+            {
+                state.refresh_rate = refresh_rate(window);
+            }
+        }
+    }
+
+    /// Every window which is open or about to be opened, ordered by id.
+    pub(crate) fn ids(&self) -> Vec<WindowId>
+    {
+        let mut ids = self.windows.lock().unwrap().keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    pub(crate) fn contains(&self, id: WindowId) -> bool
+    {
+        self.windows.lock().unwrap().contains_key(&id)
+    }
+
+    /// Maps a winit window to its id, `None` for windows which have been closed already.
+    pub(crate) fn lookup(&self, winit_id: winit::window::WindowId) -> Option<WindowId>
+    {
+        self.windows.lock().unwrap()
+            .iter()
+            .find(|(_, managed)| managed.window.as_ref().is_some_and(|w| w.id() == winit_id))
+            .map(|(id, _)| *id)
+    }
+
+    pub(crate) fn refresh_rate(&self, id: WindowId) -> Option<u32>
+    {
+        self.read_state(id, |s| s.refresh_rate)
+    }
+
+    pub(crate) fn title(&self, id: WindowId) -> String
+    {
+        self.read_state(id, |s| s.title.clone())
+    }
+
+    pub(crate) fn size(&self, id: WindowId) -> (u32, u32)
+    {
+        self.read_state(id, |s| s.size)
+    }
+
+    pub(crate) fn min_size(&self, id: WindowId) -> Option<(u32, u32)>
+    {
+        self.read_state(id, |s| s.min_size)
+    }
+
+    pub(crate) fn max_size(&self, id: WindowId) -> Option<(u32, u32)>
+    {
+        self.read_state(id, |s| s.max_size)
+    }
+
+    pub(crate) fn fullscreen(&self, id: WindowId) -> Fullscreen
+    {
+        self.read_state(id, |s| s.fullscreen)
+    }
+
+    pub(crate) fn maximized(&self, id: WindowId) -> bool
+    {
+        self.read_state(id, |s| s.maximized)
+    }
+
+    pub(crate) fn minimized(&self, id: WindowId) -> bool
+    {
+        self.read_state(id, |s| s.minimized)
+    }
+
+    pub(crate) fn decorations(&self, id: WindowId) -> bool
+    {
+        self.read_state(id, |s| s.decorations)
+    }
+
+    pub(crate) fn always_on_top(&self, id: WindowId) -> bool
+    {
+        self.read_state(id, |s| s.always_on_top)
+    }
+
+    pub(crate) fn cursor_visible(&self, id: WindowId) -> bool
+    {
+        self.read_state(id, |s| s.cursor_visible)
+    }
+
+    pub(crate) fn cursor_grab(&self, id: WindowId) -> CursorGrab
+    {
+        self.read_state(id, |s| s.cursor_grab)
+    }
+
+    pub(crate) fn cursor(&self, id: WindowId) -> CursorIcon
+    {
+        self.read_state(id, |s| s.cursor)
+    }
+
+    /// Must only be called on the event loop thread.
+    pub(crate) fn apply(&self, message: WindowMessage, target: &EventLoopWindowTarget<WindowMessage>) -> Option<WindowChange>
+    {
+        let WindowMessage {id, request} = message;
+
+        match request
+        {
+            WindowRequest::Open(settings) => self.create(id, settings, target),
+            WindowRequest::Close => self.close(id),
+            WindowRequest::Wake => None,
+
+            request => {
+                let window = self.windows.lock().unwrap()
+                    .get(&id)
+                    .and_then(|managed| managed.window.clone());
+
+                match window
+                {
+                    Some(window) => self.apply_to(id, &window, request),
+                    None => trace!("Request for closed window {id:?} dropped"),
+                }
+
+                None
+            },
+        }
+    }
+
+    fn create(&self, id: WindowId, settings: WindowSettings, target: &EventLoopWindowTarget<WindowMessage>) -> Option<WindowChange>
+    {
+        // Closed before the event loop got to open it
+        if !self.contains(id) {return None}
+
+        let mut builder = WindowBuilder::new().with_title(settings.title);
+
+        if let Some((width, height)) = settings.size
+        {
+            builder = builder.with_inner_size(PhysicalSize::new(width, height));
+        }
+
+        match builder.build(target)
+        {
+            Ok(window) => {
+                let window = Arc::new(window);
+                let size = window.inner_size();
+
+                if let Ok(mut windows) = self.windows.lock()
+                {
+                    if let Some(managed) = windows.get_mut(&id)
+                    {
+                        managed.state.size = (size.width, size.height);
+                        managed.state.refresh_rate = refresh_rate(&window);
+                        managed.window = Some(window.clone());
+                    }
+                }
+
+                info!("Window {id:?} opened");
+                Some(WindowChange::Opened(id, window))
+            },
+
+            Err(e) => {
+                error!("Failed to open window {id:?}: {e}");
+                self.windows.lock().unwrap().remove(&id);
+                None
+            },
+        }
+    }
+
+    fn close(&self, id: WindowId) -> Option<WindowChange>
+    {
+        if id == WindowId::MAIN
+        {
+            warn!("The main window can not be closed, quit the app instead");
+            return None;
+        }
+
+        // The window itself is destroyed once the renderer released it as well
+        let managed = self.windows.lock().unwrap().remove(&id)?;
+
+        info!("Window {id:?} closed");
+        managed.window.map(|_| WindowChange::Closed(id))
+    }
+
+    fn apply_to(&self, id: WindowId, window: &Window, request: WindowRequest)
+    {
+        match request
+        {
+            WindowRequest::Title(title) => window.set_title(&title),
+            WindowRequest::MinSize(size) => window.set_min_inner_size(size.map(|(w, h)| PhysicalSize::new(w, h))),
+            WindowRequest::MaxSize(size) => window.set_max_inner_size(size.map(|(w, h)| PhysicalSize::new(w, h))),
+            WindowRequest::Fullscreen(fullscreen) => apply_fullscreen(window, fullscreen),
+            WindowRequest::Maximized(maximized) => window.set_maximized(maximized),
+            WindowRequest::Minimized(minimized) => window.set_minimized(minimized),
+            WindowRequest::Decorations(decorations) => window.set_decorations(decorations),
+            WindowRequest::Icon(icon) => window.set_window_icon(icon.map(|i| i.to_winit())),
+
+            WindowRequest::Size((width, height)) => {
+                // Platforms which resize right away do not send a resize event
+                if let Some(size) = window.request_inner_size(PhysicalSize::new(width, height))
+                {
+                    self.set_size(id, (size.width, size.height));
+                }
+            },
+
+            WindowRequest::AlwaysOnTop(on_top) => {
+                let level = if on_top {WindowLevel::AlwaysOnTop} else {WindowLevel::Normal};
+                window.set_window_level(level);
+            },
+
+            WindowRequest::CursorVisible(visible) => window.set_cursor_visible(visible),
+            WindowRequest::CursorGrab(grab) => apply_grab(window, grab),
+
+            WindowRequest::CursorPosition((x, y)) => {
+                if let Err(e) = window.set_cursor_position(PhysicalPosition::new(x, y))
+                {
+                    warn!("Failed to move the cursor: {e}");
+                }
+            },
+
+            WindowRequest::Cursor(icon) => window.set_cursor_icon(icon_to_winit(icon)),
+
+            WindowRequest::Open(_) | WindowRequest::Close | WindowRequest::Wake => {},
+        }
+    }
+}
+
+
+fn refresh_rate(window: &Window) -> Option<u32>
+{
+    window.current_monitor()
+        .and_then(|monitor| monitor.refresh_rate_millihertz())
+}
+
+
+fn apply_fullscreen(window: &Window, fullscreen: Fullscreen)
+{
+    let mode = match fullscreen
+    {
+        Fullscreen::Windowed => None,
+        Fullscreen::Borderless => Some(winit::window::Fullscreen::Borderless(None)),
+
+        Fullscreen::Exclusive => {
+            let video_mode = window.current_monitor()
+                .and_then(|m| m.video_modes().max_by_key(|v| (v.size().width * v.size().height, v.refresh_rate_millihertz())));
+
+            match video_mode
+            {
+                Some(video_mode) => Some(winit::window::Fullscreen::Exclusive(video_mode)),
+
+                None => {
+                    warn!("No video mode available for exclusive fullscreen, using borderless fullscreen");
+                    Some(winit::window::Fullscreen::Borderless(None))
+                },
+            }
+        },
+    };
+
+    window.set_fullscreen(mode);
+}
+
+
+/// Platforms support either confining or locking, the other mode is used as fallback.
+fn apply_grab(window: &Window, grab: CursorGrab)
+{
+    let (mode, fallback) = match grab
+    {
+        CursorGrab::None => (CursorGrabMode::None, None),
+        CursorGrab::Confined => (CursorGrabMode::Confined, Some(CursorGrabMode::Locked)),
+        CursorGrab::Locked => (CursorGrabMode::Locked, Some(CursorGrabMode::Confined)),
+    };
+
+    let result = window.set_cursor_grab(mode)
+        .or_else(|e| match fallback
+        {
+            Some(fallback) => window.set_cursor_grab(fallback),
+            None => Err(e),
+        });
+
+    if let Err(e) = result
+    {
+        warn!("Failed to set cursor grab {grab:?}: {e}");
+    }
+}
+
+
+fn icon_to_winit(icon: CursorIcon) -> winit::window::CursorIcon
+{
+    use winit::window::CursorIcon as W;
+
+    match icon
+    {
+        CursorIcon::Default => W::Default,
+        CursorIcon::Pointer => W::Pointer,
+        CursorIcon::Text => W::Text,
+        CursorIcon::Crosshair => W::Crosshair,
+        CursorIcon::Move => W::Move,
+        CursorIcon::Grab => W::Grab,
+        CursorIcon::Grabbing => W::Grabbing,
+        CursorIcon::NotAllowed => W::NotAllowed,
+        CursorIcon::Wait => W::Wait,
+        CursorIcon::ResizeHorizontal => W::EwResize,
+        CursorIcon::ResizeVertical => W::NsResize,
+    }
+}